@@ -0,0 +1,67 @@
+// Serialization cost of the columnar indicator wrappers versus the default
+// array-of-objects output.
+//
+// Both variants run the same indicator over the same input, so the timing
+// difference is the cost of building the result in JS. No numbers are
+// checked in and CI does not run this, as it needs the Node bindings. Build
+// them (wasm-pack, or cargo plus a wasm-bindgen CLI matching the locked
+// wasm-bindgen version), then run from `wasm/`:
+//
+//   wasm-pack build --release --target nodejs --out-dir bench/pkg
+//   node bench/columnar.mjs
+
+import { createRequire } from "node:module";
+
+const require = createRequire(import.meta.url);
+const wasm = require("./pkg/stocks_wasm.js");
+
+const SIZES = [1_000, 10_000, 100_000];
+const PERIOD = 20;
+
+function bars(n) {
+  return Array.from({ length: n }, (_, i) => {
+    const close = 100 + Math.sin(i / 50) * 10 + i * 0.001;
+    return {
+      ts: 1_700_000_000_000 + i * 60_000,
+      open: close - 0.25,
+      high: close + 0.5,
+      low: close - 0.5,
+      close,
+      volume: 1_000 + (i % 100),
+    };
+  });
+}
+
+// Median of per-call milliseconds, after a short warm-up.
+function time(fn) {
+  for (let i = 0; i < 3; i++) fn();
+  const runs = [];
+  const deadline = performance.now() + 1_000;
+  while (runs.length < 5 || (performance.now() < deadline && runs.length < 200)) {
+    const start = performance.now();
+    fn();
+    runs.push(performance.now() - start);
+  }
+  runs.sort((a, b) => a - b);
+  return runs[Math.floor(runs.length / 2)];
+}
+
+const rows = [];
+for (const n of SIZES) {
+  const data = bars(n);
+  const objects = wasm.calc_sma(data, PERIOD);
+  const columns = wasm.calc_sma_columnar(data, PERIOD);
+  const objectMs = time(() => wasm.calc_sma(data, PERIOD));
+  const columnarMs = time(() => wasm.calc_sma_columnar(data, PERIOD));
+  rows.push({
+    bars: n,
+    "objects ms": objectMs.toFixed(3),
+    "columnar ms": columnarMs.toFixed(3),
+    speedup: (objectMs / columnarMs).toFixed(2),
+    "to_columnar ms": time(() => wasm.to_columnar(objects)).toFixed(3),
+    "objects JSON bytes": JSON.stringify(objects).length,
+    "columnar bytes": columns.ts.byteLength + columns.value.byteLength,
+  });
+}
+
+console.table(rows);
@@ -0,0 +1,94 @@
+//! Prefix queries through `SymbolIndex::search` versus the linear
+//! `filter_symbols_impl` scan, over a synthetic universe of 50k symbols.
+//!
+//! Run with `cargo bench --bench symbol_search`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use stocks_wasm::{filter_symbols_impl, SymbolEntry, SymbolIndex};
+
+const UNIVERSE: usize = 50_000;
+const QUERIES: [&str; 6] = ["a", "ab", "aab", "ms", "zq", "holdings"];
+const MAX_RESULTS: [usize; 2] = [10, 50];
+
+/// Deterministic tickers of one to five letters with a few ranked names,
+/// skewed toward early letters like a real listing.
+fn universe() -> Vec<SymbolEntry> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..UNIVERSE)
+        .map(|i| {
+            let len = 1 + (next() % 5) as usize;
+            let symbol: String = (0..len)
+                .map(|_| {
+                    // Square-root skew: 'A' is about 50x likelier than 'Z'.
+                    let r = next() % 676;
+                    (b'Z' - (r as f64).sqrt() as u8) as char
+                })
+                .collect();
+            SymbolEntry {
+                name: format!("{} Holdings Inc", symbol),
+                symbol,
+                rank: (i % 10 == 0).then_some((i / 10) as u32),
+            }
+        })
+        .collect()
+}
+
+/// Median wall time of `f` over repeated runs, after a warm-up.
+fn median(mut f: impl FnMut()) -> Duration {
+    for _ in 0..3 {
+        f();
+    }
+    let mut runs: Vec<Duration> = Vec::new();
+    let deadline = Instant::now() + Duration::from_millis(500);
+    while runs.len() < 5 || (Instant::now() < deadline && runs.len() < 500) {
+        let start = Instant::now();
+        f();
+        runs.push(start.elapsed());
+    }
+    runs.sort();
+    runs[runs.len() / 2]
+}
+
+fn main() {
+    let entries = universe();
+    let index = SymbolIndex::from_entries(entries.clone());
+
+    println!(
+        "{:<8} {:>5} {:>12} {:>12} {:>8}",
+        "query", "max", "linear", "index", "speedup"
+    );
+    for query in QUERIES {
+        for max_results in MAX_RESULTS {
+            assert_eq!(
+                index.search(query, max_results),
+                filter_symbols_impl(&entries, query, max_results)
+            );
+            let linear = median(|| {
+                black_box(filter_symbols_impl(
+                    black_box(&entries),
+                    black_box(query),
+                    max_results,
+                ));
+            });
+            let indexed = median(|| {
+                black_box(index.search(black_box(query), max_results));
+            });
+            println!(
+                "{:<8} {:>5} {:>10.1?} {:>10.1?} {:>7.1}x",
+                query,
+                max_results,
+                linear,
+                indexed,
+                linear.as_secs_f64() / indexed.as_secs_f64()
+            );
+        }
+    }
+}
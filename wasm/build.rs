@@ -0,0 +1,6 @@
+// Forward the compilation target triple so `build_info()` can report it.
+fn main() {
+    let target = std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=STOCKS_WASM_TARGET={}", target);
+    println!("cargo:rerun-if-changed=build.rs");
+}
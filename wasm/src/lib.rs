@@ -1,3 +1,5 @@
+use std::collections::{BTreeMap, VecDeque};
+
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
@@ -31,25 +33,685 @@ pub struct IndicatorPoint {
 pub struct SymbolEntry {
     pub symbol: String,
     pub name: String,
+    /// Popularity rank, lower is more popular. Breaks score ties in search.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u32>,
+}
+
+/// Column-oriented OHLCV bars as sent by the backend to keep payloads small.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct OhlcvColumns {
+    pub ts: Vec<f64>,
+    pub open: Vec<f64>,
+    pub high: Vec<f64>,
+    pub low: Vec<f64>,
+    pub close: Vec<f64>,
+    pub volume: Vec<f64>,
+}
+
+/// Column-oriented indicator output, sent to JS as two `Float64Array`s
+/// instead of an array of `{ts, value}` objects. `bench/columnar.mjs`
+/// compares the two on a Node build.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct IndicatorColumns {
+    pub ts: Vec<f64>,
+    pub value: Vec<f64>,
+}
+
+/// How RSI averages gains and losses after the initial seed window.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmoothingMethod {
+    Wilder,
+    Ema,
+    Sma,
+}
+
+impl SmoothingMethod {
+    /// Parse a case-insensitive method name (`"wilder"`, `"ema"`, `"sma"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "wilder" => Some(Self::Wilder),
+            "ema" => Some(Self::Ema),
+            "sma" => Some(Self::Sma),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct ScoredEntry {
     symbol: String,
     name: String,
+    rank: Option<u32>,
+    tier: i32,
     score: i32,
 }
 
+/// A search hit from `filter_symbols_multi`, tagged with its universe.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TaggedEntry {
+    pub symbol: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u32>,
+    /// Name of the universe the entry came from.
+    pub source: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SwingKind {
+    High,
+    Low,
+}
+
+/// A local extreme (pivot) in the price series.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Swing {
+    pub ts: f64,
+    pub index: usize,
+    pub price: f64,
+    pub kind: SwingKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DivergenceKind {
+    /// Price made a lower low while RSI made a higher low.
+    Bullish,
+    /// Price made a higher high while RSI made a lower high.
+    Bearish,
+}
+
+/// Disagreement between two consecutive price swings and RSI at those bars.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DivergenceEvent {
+    pub first_ts: f64,
+    pub second_ts: f64,
+    pub kind: DivergenceKind,
+}
+
+/// The previous session's range and close, drawn as reference lines on the
+/// session starting at `session_ts`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SessionLevels {
+    pub session_ts: f64,
+    pub prev_high: f64,
+    pub prev_low: f64,
+    pub prev_close: f64,
+}
+
+/// One fixed-size Renko brick, stamped with the bar that completed it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RenkoBrick {
+    pub ts: f64,
+    pub open: f64,
+    pub close: f64,
+    pub up: bool,
+}
+
+/// A horizontal support/resistance level built from nearby swings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LevelCluster {
+    pub price: f64,
+    pub touches: usize,
+}
+
+/// Which per-bar value an indicator reads.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceSource {
+    Open,
+    High,
+    Low,
+    Close,
+    Volume,
+    /// (high + low) / 2
+    Median,
+    /// (high + low + close) / 3
+    Typical,
+    /// (high + low + 2 * close) / 4
+    WeightedClose,
+    /// (open + high + low + close) / 4
+    Ohlc4,
+}
+
+impl PriceSource {
+    /// Parse a case-insensitive source name. Accepts the TradingView-style
+    /// aliases `hl2`, `hlc3` and `hlcc4` alongside the descriptive names.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "open" => Some(Self::Open),
+            "high" => Some(Self::High),
+            "low" => Some(Self::Low),
+            "close" => Some(Self::Close),
+            "volume" => Some(Self::Volume),
+            "median" | "hl2" => Some(Self::Median),
+            "typical" | "hlc3" => Some(Self::Typical),
+            "weighted_close" | "hlcc4" => Some(Self::WeightedClose),
+            "ohlc4" => Some(Self::Ohlc4),
+            _ => None,
+        }
+    }
+
+    /// Read this source from a bar.
+    pub fn value(self, p: &PricePoint) -> f64 {
+        match self {
+            Self::Open => p.open,
+            Self::High => p.high,
+            Self::Low => p.low,
+            Self::Close => p.close,
+            Self::Volume => p.volume,
+            Self::Median => (p.high + p.low) / 2.0,
+            Self::Typical => (p.high + p.low + p.close) / 3.0,
+            Self::WeightedClose => (p.high + p.low + 2.0 * p.close) / 4.0,
+            Self::Ohlc4 => (p.open + p.high + p.low + p.close) / 4.0,
+        }
+    }
+}
+
+/// One entry of a batch `compute_indicators` request.
+///
+/// `kind` is one of `sma`, `ema`, `rsi` or `vwap`; `period` is ignored for
+/// VWAP. Results are keyed by `key`, defaulting to e.g. `"sma_20"`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IndicatorSpec {
+    pub kind: String,
+    #[serde(default)]
+    pub period: usize,
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+impl IndicatorSpec {
+    /// Key this spec's output is stored under.
+    pub fn result_key(&self) -> String {
+        match &self.key {
+            Some(key) => key.clone(),
+            None if self.kind == "vwap" => self.kind.clone(),
+            None => format!("{}_{}", self.kind, self.period),
+        }
+    }
+}
+
+/// Batch results plus per-indicator wall time in microseconds.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TimedIndicators {
+    pub results: BTreeMap<String, Vec<IndicatorPoint>>,
+    pub timings: BTreeMap<String, f64>,
+}
+
+/// Klinger Volume Oscillator output: the KVO line and its EMA signal.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct KlingerResult {
+    pub kvo: Vec<IndicatorPoint>,
+    pub signal: Vec<IndicatorPoint>,
+}
+
+/// Fisher Transform output: the fisher line and its one-bar-lagged trigger.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FisherResult {
+    pub fisher: Vec<IndicatorPoint>,
+    pub trigger: Vec<IndicatorPoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    Doji,
+    BullishEngulfing,
+    BearishEngulfing,
+    Hammer,
+    ShootingStar,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PatternHit {
+    pub ts: f64,
+    pub pattern: PatternKind,
+}
+
+/// Tunable ratios for `detect_patterns_impl`. Missing fields take the
+/// defaults when deserialized from JS.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct PatternThresholds {
+    /// Doji when body / range is at most this.
+    pub doji_body_ratio: f64,
+    /// Hammer / shooting star body / range must be at most this.
+    pub hammer_body_ratio: f64,
+    /// Hammer / shooting star long wick must be at least this many bodies.
+    pub hammer_wick_ratio: f64,
+    /// Hammer / shooting star short wick / range must be at most this.
+    pub hammer_opposite_wick_ratio: f64,
+}
+
+impl Default for PatternThresholds {
+    fn default() -> Self {
+        PatternThresholds {
+            doji_body_ratio: 0.1,
+            hammer_body_ratio: 0.35,
+            hammer_wick_ratio: 2.0,
+            hammer_opposite_wick_ratio: 0.1,
+        }
+    }
+}
+
+/// A page of symbol search results and the total number of matches.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CountedSymbols {
+    pub results: Vec<SymbolEntry>,
+    pub total: usize,
+}
+
+/// Elder Ray output: high and low measured against the close EMA.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ElderRayResult {
+    pub bull_power: Vec<IndicatorPoint>,
+    pub bear_power: Vec<IndicatorPoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TsUnit {
+    Seconds,
+    Millis,
+}
+
+/// A hole in the bar sequence between two consecutive bars.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Gap {
+    pub start_ts: f64,
+    pub end_ts: f64,
+    pub missing_bars: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GapKind {
+    GapUp,
+    GapDown,
+}
+
+/// A price discontinuity: a bar opening away from the prior close.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GapEvent {
+    pub ts: f64,
+    pub kind: GapKind,
+    /// `open / prev_close - 1`, negative for a gap down.
+    pub pct: f64,
+}
+
+/// How an EMA chooses its starting value.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmaSeed {
+    /// SMA of the first `period` closes; output starts at bar `period - 1`.
+    #[default]
+    SmaWindow,
+    /// The first close itself; output starts at bar 0.
+    FirstValue,
+}
+
+impl EmaSeed {
+    /// Parse a case-insensitive seed name (`"sma"`, `"first"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sma" | "sma_window" => Some(Self::SmaWindow),
+            "first" | "first_value" => Some(Self::FirstValue),
+            _ => None,
+        }
+    }
+}
+
+/// Moving-average flavour for composite indicators.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MaKind {
+    #[default]
+    Sma,
+    Ema,
+}
+
+impl MaKind {
+    /// Parse a case-insensitive kind name (`"sma"`, `"ema"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sma" => Some(Self::Sma),
+            "ema" => Some(Self::Ema),
+            _ => None,
+        }
+    }
+
+    /// Compute this moving average over `data` closes.
+    pub fn compute(self, data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
+        match self {
+            Self::Sma => calc_sma_impl(data, period),
+            Self::Ema => calc_ema_impl(data, period),
+        }
+    }
+}
+
+/// Normalization applied by `normalize_series_impl`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NormMode {
+    /// Scale to 0..1 over the whole series.
+    #[default]
+    MinMax,
+    /// Subtract the mean and divide by the population standard deviation.
+    ZScore,
+}
+
+impl NormMode {
+    /// Parse a case-insensitive mode name (`"minmax"`, `"zscore"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "minmax" | "min_max" => Some(Self::MinMax),
+            "zscore" | "z_score" => Some(Self::ZScore),
+            _ => None,
+        }
+    }
+}
+
+/// How `align_series_impl` combines two timestamped series.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Only timestamps present in both series.
+    #[default]
+    Inner,
+    /// Union of timestamps, with `None` where a series lacks a bar.
+    Outer,
+}
+
+/// Closes of two series at a shared timestamp.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AlignedCloses {
+    pub ts: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// `DataPoint` with an integer timestamp, so millisecond epochs survive the
+/// JS boundary bit-exactly.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DataPointI {
+    pub ts: i64,
+    pub value: f64,
+}
+
+/// `PricePoint` with an integer timestamp.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PricePointI {
+    pub ts: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+/// `IndicatorPoint` with an integer timestamp.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IndicatorPointI {
+    pub ts: i64,
+    pub value: f64,
+}
+
+/// Which bar `dedupe_timestamps_impl` keeps for a repeated timestamp.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DedupePolicy {
+    #[default]
+    KeepLast,
+    KeepFirst,
+    /// OHLC-merge: first open, last close, max high, min low, summed volume.
+    Merge,
+}
+
+impl DedupePolicy {
+    /// Parse a case-insensitive policy name (`"last"`, `"first"`, `"merge"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "last" | "keep_last" => Some(Self::KeepLast),
+            "first" | "keep_first" => Some(Self::KeepFirst),
+            "merge" => Some(Self::Merge),
+            _ => None,
+        }
+    }
+}
+
+/// What a `Signal` reports; each event detector emits its own kinds.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    /// Fast MA crossed above slow MA.
+    GoldenCross,
+    /// Fast MA crossed below slow MA.
+    DeathCross,
+    /// Parabolic SAR flipped below price.
+    PsarBuy,
+    /// Parabolic SAR flipped above price.
+    PsarSell,
+}
+
+impl SignalKind {
+    /// Whether the signal calls for a long position.
+    pub fn is_bullish(self) -> bool {
+        matches!(self, Self::GoldenCross | Self::PsarBuy)
+    }
+}
+
+/// A discrete event on bar `index`, shared by every signal detector so
+/// streams can be merged into one strategy view. `strength` is the
+/// detector's relative separation at the event (see each detector).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Signal {
+    pub ts: f64,
+    pub index: usize,
+    pub kind: SignalKind,
+    pub strength: f64,
+}
+
+/// Volume traded per price bucket over a set of bars.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct VolumeProfile {
+    /// Bucket midpoints, ascending.
+    pub prices: Vec<f64>,
+    /// Summed volume per bucket, parallel to `prices`.
+    pub volumes: Vec<f64>,
+    pub bin_size: f64,
+    /// Point of Control: index of the highest-volume bucket.
+    pub poc_index: usize,
+    pub poc_price: f64,
+}
+
+/// Price range around the Point of Control holding a share of the volume.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ValueArea {
+    pub high: f64,
+    pub low: f64,
+    pub volume: f64,
+}
+
+/// Bollinger Bands: SMA middle band with bands `num_std` deviations away.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BollingerResult {
+    pub upper: Vec<IndicatorPoint>,
+    pub middle: Vec<IndicatorPoint>,
+    pub lower: Vec<IndicatorPoint>,
+}
+
+/// Keltner Channels: EMA middle line with bands a multiple of ATR away.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct KeltnerResult {
+    pub upper: Vec<IndicatorPoint>,
+    pub middle: Vec<IndicatorPoint>,
+    pub lower: Vec<IndicatorPoint>,
+}
+
+/// Why a bar failed `validate_ohlc_impl`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OhlcIssue {
+    /// A timestamp, price or volume is NaN or infinite.
+    NonFinite,
+    HighBelowLow,
+    OpenOutOfRange,
+    CloseOutOfRange,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OhlcError {
+    pub index: usize,
+    pub reason: OhlcIssue,
+}
+
+/// Quick sanity summary of an indicator series for debugging.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SeriesStats {
+    pub count: usize,
+    pub first_ts: f64,
+    pub last_ts: f64,
+    /// Min, max and mean of the finite values only.
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Values that are NaN or infinite.
+    pub nan_count: usize,
+}
+
+/// True Strength Index and its EMA signal line.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TsiResult {
+    pub tsi: Vec<IndicatorPoint>,
+    pub signal: Vec<IndicatorPoint>,
+}
+
+/// Moving-average envelope: bands a fixed percentage above and below the MA.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct EnvelopeResult {
+    pub upper: Vec<IndicatorPoint>,
+    pub middle: Vec<IndicatorPoint>,
+    pub lower: Vec<IndicatorPoint>,
+}
+
+/// Where a query may match inside a symbol's name.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Anywhere in the name, mid-word included.
+    #[default]
+    Anywhere,
+    /// Only at the start of a word in the name.
+    WordBoundary,
+}
+
+impl MatchMode {
+    /// Parse a case-insensitive mode name (`"anywhere"`, `"word"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "anywhere" => Some(Self::Anywhere),
+            "word" | "word_boundary" => Some(Self::WordBoundary),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of a simulated strategy run.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct BacktestResult {
+    /// Marked-to-market equity per bar, starting at 1.0.
+    pub equity: Vec<IndicatorPoint>,
+    /// Final equity minus 1.
+    pub total_return: f64,
+    /// Completed round trips.
+    pub trades: usize,
+    /// Fraction of trades that closed at a profit; 0 with no trades.
+    pub win_rate: f64,
+    /// Largest peak-to-trough equity decline, as a positive fraction.
+    pub max_drawdown: f64,
+}
+
+/// Headline performance figures for one symbol.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SummaryStats {
+    pub total_return: f64,
+    pub cagr: f64,
+    /// Annualized standard deviation of close-to-close returns.
+    pub volatility: f64,
+    /// Positive fraction; see `max_drawdown_over_values`.
+    pub max_drawdown: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Build info
+// ---------------------------------------------------------------------------
+
+/// Crate version, baked in at compile time.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Target triple the crate was compiled for (set by `build.rs`).
+pub const TARGET: &str = env!("STOCKS_WASM_TARGET");
+
+/// `{"version": ..., "target": ...}` as a compile-time JSON string.
+pub const BUILD_INFO: &str = concat!(
+    "{\"version\":\"",
+    env!("CARGO_PKG_VERSION"),
+    "\",\"target\":\"",
+    env!("STOCKS_WASM_TARGET"),
+    "\"}"
+);
+
 // ---------------------------------------------------------------------------
 // Internal (pure-Rust) implementations — testable without wasm_bindgen
 // ---------------------------------------------------------------------------
 
+/// Relative margin by which a later point's triangle area must beat the
+/// current best to replace it in LTTB. Areas closer than this count as a tie
+/// and keep the earlier index, so last-bit rounding differences between
+/// native and wasm builds cannot flip the selection.
+const LTTB_TIE_EPSILON: f64 = 1e-9;
+
+/// Mean of `points` as an offset `(dts, dvalue)` from `anchor`. Offsets are
+/// taken before summing, so epoch-millisecond timestamps do not round away
+/// the sub-millisecond part of the mean.
+fn lttb_mean_offset(anchor: &DataPoint, points: &[DataPoint]) -> (f64, f64) {
+    let n = points.len().max(1) as f64;
+    let (dts, dvalue) = points.iter().fold((0.0, 0.0), |(t, v), p| {
+        (t + (p.ts - anchor.ts), v + (p.value - anchor.value))
+    });
+    (dts / n, dvalue / n)
+}
+
+/// Doubled area of the triangle `anchor`, `p` and the point at `offset`
+/// from `anchor`, computed on offsets from `anchor` rather than raw
+/// coordinates.
+fn lttb_area(anchor: &DataPoint, p: &DataPoint, offset: (f64, f64)) -> f64 {
+    let (dts, dvalue) = offset;
+    ((p.ts - anchor.ts) * dvalue - dts * (p.value - anchor.value)).abs()
+}
+
 /// Largest Triangle Three Buckets downsampling algorithm.
 ///
 /// Given a time-series of (ts, value) pairs and a target `threshold` count,
 /// returns a visually representative subset that preserves the shape of the
 /// original series.
 pub fn lttb_downsample_impl(data: &[DataPoint], threshold: usize) -> Vec<DataPoint> {
+    lttb_downsample_indices_impl(data, threshold)
+        .into_iter()
+        .map(|i| data[i].clone())
+        .collect()
+}
+
+/// First interior index of LTTB bucket `i` when `len` points are reduced to
+/// `threshold`: `1 + floor(i * (len - 2) / (threshold - 2))`. Integer
+/// arithmetic keeps the boundaries exact, so near `threshold == len - 1` no
+/// bucket comes out empty and the last interior point is never dropped.
+/// The product is taken in `u64` because `i * (len - 2)` passes
+/// `u32::MAX` on wasm32 for inputs of a few hundred thousand points.
+fn lttb_bucket_start(i: usize, len: usize, threshold: usize) -> usize {
+    1 + (i as u64 * (len - 2) as u64 / (threshold - 2) as u64) as usize
+}
+
+/// Indices of the points `lttb_downsample_impl` keeps, strictly increasing
+/// and always including `0` and `len - 1`, so callers can link the
+/// downsampled view back to the full dataset.
+pub fn lttb_downsample_indices_impl(data: &[DataPoint], threshold: usize) -> Vec<usize> {
     let len = data.len();
 
     // Edge cases: nothing to downsample.
@@ -57,79 +719,167 @@ pub fn lttb_downsample_impl(data: &[DataPoint], threshold: usize) -> Vec<DataPoi
         return Vec::new();
     }
     if threshold < 3 || len <= threshold {
-        return data.to_vec();
+        return (0..len).collect();
     }
 
-    let mut result: Vec<DataPoint> = Vec::with_capacity(threshold);
+    let mut result: Vec<usize> = Vec::with_capacity(threshold);
 
     // Always keep the first point.
-    result.push(data[0].clone());
-
-    // Bucket size (the first and last points are fixed, so we distribute
-    // the remaining threshold-2 buckets across the interior points).
-    let bucket_size: f64 = (len - 2) as f64 / (threshold - 2) as f64;
+    result.push(0);
 
+    // The first and last points are fixed, so the remaining threshold-2
+    // buckets are distributed across the interior points. Since
+    // len > threshold every bucket holds at least one point.
     let mut prev_selected: usize = 0;
 
     for i in 0..(threshold - 2) {
         // Current bucket range
-        let bucket_start = ((i as f64 * bucket_size) + 1.0).floor() as usize;
-        let bucket_end = (((i + 1) as f64 * bucket_size) + 1.0).floor().min(len as f64) as usize;
+        let bucket_start = lttb_bucket_start(i, len, threshold);
+        let bucket_end = lttb_bucket_start(i + 1, len, threshold);
 
         // Next bucket range (used to compute the average point)
-        let next_bucket_start = (((i + 1) as f64 * bucket_size) + 1.0).floor() as usize;
-        let next_bucket_end =
-            (((i + 2) as f64 * bucket_size) + 1.0).floor().min(len as f64) as usize;
+        let next_bucket_start = bucket_end;
+        let next_bucket_end = lttb_bucket_start(i + 2, len, threshold).min(len);
 
-        // Average point of the *next* bucket
-        let mut avg_ts: f64 = 0.0;
-        let mut avg_val: f64 = 0.0;
-        let next_bucket_len = (next_bucket_end - next_bucket_start).max(1);
-        for j in next_bucket_start..next_bucket_end.min(len) {
-            avg_ts += data[j].ts;
-            avg_val += data[j].value;
-        }
-        avg_ts /= next_bucket_len as f64;
-        avg_val /= next_bucket_len as f64;
+        // Average point of the *next* bucket, relative to the previously
+        // selected point.
+        let prev = &data[prev_selected];
+        let avg = lttb_mean_offset(prev, &data[next_bucket_start..next_bucket_end.min(len)]);
 
         // Select the point in the current bucket that forms the largest
         // triangle with the previously selected point and the average of
-        // the next bucket.
+        // the next bucket. Ties — including areas that differ only by
+        // rounding noise (see `LTTB_TIE_EPSILON`) — go to the earlier index.
         let mut max_area: f64 = -1.0;
         let mut max_idx: usize = bucket_start;
 
-        let prev_ts = data[prev_selected].ts;
-        let prev_val = data[prev_selected].value;
-
-        for j in bucket_start..bucket_end.min(len) {
+        for (j, p) in data
+            .iter()
+            .enumerate()
+            .take(bucket_end.min(len))
+            .skip(bucket_start)
+        {
             // Triangle area (doubled, sign doesn't matter — we want max abs).
-            let area = ((prev_ts - avg_ts) * (data[j].value - prev_val)
-                - (prev_ts - data[j].ts) * (avg_val - prev_val))
-                .abs();
+            let area = lttb_area(prev, p, avg);
 
-            if area > max_area {
+            if area > max_area + LTTB_TIE_EPSILON * max_area {
                 max_area = area;
                 max_idx = j;
             }
         }
 
-        result.push(data[max_idx].clone());
+        result.push(max_idx);
         prev_selected = max_idx;
     }
 
     // Always keep the last point.
+    result.push(len - 1);
+
+    result
+}
+
+/// LTTB with buckets of equal time span instead of equal point count.
+///
+/// The interior points are split into `threshold - 2` slices of
+/// `[first_ts, last_ts]`, so dense stretches of irregular (e.g. tick) data
+/// are not over-sampled. Slices holding no points, such as market-closed
+/// hours, are skipped, so the output can be shorter than `threshold`.
+/// Expects ascending timestamps; a zero time span falls back to
+/// `lttb_downsample_impl`.
+pub fn lttb_downsample_time_impl(data: &[DataPoint], threshold: usize) -> Vec<DataPoint> {
+    let len = data.len();
+    if threshold < 3 || len <= threshold {
+        return data.to_vec();
+    }
+    let (first_ts, last_ts) = (data[0].ts, data[len - 1].ts);
+    let span = last_ts - first_ts;
+    if !span.is_finite() || span <= 0.0 {
+        return lttb_downsample_impl(data, threshold);
+    }
+
+    // Contiguous interior ranges, one per non-empty time slice.
+    let slices = threshold - 2;
+    let mut buckets: Vec<(usize, usize)> = Vec::new();
+    let mut last_slice = usize::MAX;
+    for (i, p) in data.iter().enumerate().take(len - 1).skip(1) {
+        let slice = (((p.ts - first_ts) / span * slices as f64) as usize).min(slices - 1);
+        if slice == last_slice {
+            buckets.last_mut().expect("bucket started").1 = i + 1;
+        } else {
+            buckets.push((i, i + 1));
+            last_slice = slice;
+        }
+    }
+
+    let mut result = Vec::with_capacity(buckets.len() + 2);
+    result.push(data[0].clone());
+    let mut prev = &data[0];
+    for (k, &(start, end)) in buckets.iter().enumerate() {
+        let next = match buckets.get(k + 1) {
+            Some(&(ns, ne)) => &data[ns..ne],
+            None => &data[len - 1..],
+        };
+        let avg = lttb_mean_offset(prev, next);
+
+        let mut max_area: f64 = -1.0;
+        let mut max_idx = start;
+        for (j, p) in data.iter().enumerate().take(end).skip(start) {
+            let area = lttb_area(prev, p, avg);
+            if area > max_area + LTTB_TIE_EPSILON * max_area {
+                max_area = area;
+                max_idx = j;
+            }
+        }
+
+        result.push(data[max_idx].clone());
+        prev = &data[max_idx];
+    }
     result.push(data[len - 1].clone());
 
     result
 }
 
+/// LTTB with the selection made on a smoothed copy of the values.
+///
+/// Each value is first replaced by the mean of a centered `smooth_window`
+/// (shrunk at the edges), which stops noisy buckets from each jumping to
+/// their own spike. Only the choice of indices uses the smoothed series; the
+/// returned points carry the original values, so kept extremes are not
+/// flattened. `smooth_window` of 0 or 1 is plain LTTB.
+pub fn lttb_downsample_smoothed_impl(
+    data: &[DataPoint],
+    threshold: usize,
+    smooth_window: usize,
+) -> Vec<DataPoint> {
+    if smooth_window <= 1 {
+        return lttb_downsample_impl(data, threshold);
+    }
+
+    let before = smooth_window / 2;
+    let after = (smooth_window - 1) / 2;
+    let smoothed: Vec<DataPoint> = (0..data.len())
+        .map(|i| {
+            let window = &data[i.saturating_sub(before)..(i + after + 1).min(data.len())];
+            DataPoint {
+                ts: data[i].ts,
+                value: window.iter().map(|p| p.value).sum::<f64>() / window.len() as f64,
+            }
+        })
+        .collect();
+
+    lttb_downsample_indices_impl(&smoothed, threshold)
+        .into_iter()
+        .map(|i| data[i].clone())
+        .collect()
+}
+
 /// Simple Moving Average over close prices.
 pub fn calc_sma_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
     if period == 0 || period > data.len() {
         return Vec::new();
     }
 
-    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(data.len() - period + 1);
+    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(data.len().saturating_sub(period) + 1);
 
     // Initial window sum.
     let mut window_sum: f64 = data[..period].iter().map(|p| p.close).sum();
@@ -164,17 +914,17 @@ pub fn calc_ema_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint>
     // Seed: SMA of first `period` closes.
     let sma: f64 = data[..period].iter().map(|p| p.close).sum::<f64>() / period as f64;
 
-    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(data.len() - period + 1);
+    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(data.len().saturating_sub(period) + 1);
     result.push(IndicatorPoint {
         ts: data[period - 1].ts,
         value: sma,
     });
 
     let mut prev_ema = sma;
-    for i in period..data.len() {
-        let ema = data[i].close * k + prev_ema * (1.0 - k);
+    for p in &data[period..] {
+        let ema = p.close * k + prev_ema * (1.0 - k);
         result.push(IndicatorPoint {
-            ts: data[i].ts,
+            ts: p.ts,
             value: ema,
         });
         prev_ema = ema;
@@ -183,78 +933,166 @@ pub fn calc_ema_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint>
     result
 }
 
-/// Relative Strength Index using Wilder's smoothing.
+/// Rolling simple average over an arbitrary value series.
 ///
-/// Returns values in the 0..=100 range. If all changes are gains the RSI is
-/// 100; if all are losses the RSI is 0.
-pub fn calc_rsi_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
-    if period == 0 || data.len() < period + 1 {
+/// `out[j]` corresponds to `values[period - 1 + j]`; empty when `period` is
+/// zero or exceeds the input.
+pub fn sma_over_values(values: &[f64], period: usize) -> Vec<f64> {
+    if period == 0 || period > values.len() {
         return Vec::new();
     }
 
-    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(data.len() - period);
-
-    // Compute initial average gain / loss over the first `period` changes.
-    let mut avg_gain: f64 = 0.0;
-    let mut avg_loss: f64 = 0.0;
-
-    for i in 1..=period {
-        let change = data[i].close - data[i - 1].close;
-        if change > 0.0 {
-            avg_gain += change;
-        } else {
-            avg_loss += change.abs();
-        }
+    let mut window_sum: f64 = values[..period].iter().sum();
+    let mut result: Vec<f64> = Vec::with_capacity(values.len() - period + 1);
+    result.push(window_sum / period as f64);
+    for i in period..values.len() {
+        window_sum += values[i] - values[i - period];
+        result.push(window_sum / period as f64);
     }
 
-    avg_gain /= period as f64;
-    avg_loss /= period as f64;
-
-    // First RSI value.
-    let rsi = if avg_loss == 0.0 {
-        100.0
-    } else if avg_gain == 0.0 {
-        0.0
-    } else {
-        let rs = avg_gain / avg_loss;
-        100.0 - (100.0 / (1.0 + rs))
-    };
+    result
+}
+
+/// EMA over an arbitrary value series, seeded like `calc_ema_impl` with the
+/// SMA of the first `period` values.
+///
+/// `out[j]` corresponds to `values[period - 1 + j]`; empty when `period` is
+/// zero or exceeds the input.
+pub fn ema_over_values(values: &[f64], period: usize) -> Vec<f64> {
+    if period == 0 || period > values.len() {
+        return Vec::new();
+    }
+
+    let k: f64 = 2.0 / (period + 1) as f64;
+    let mut prev_ema: f64 = values[..period].iter().sum::<f64>() / period as f64;
+
+    let mut result: Vec<f64> = Vec::with_capacity(values.len() - period + 1);
+    result.push(prev_ema);
+    for v in &values[period..] {
+        prev_ema = v * k + prev_ema * (1.0 - k);
+        result.push(prev_ema);
+    }
+
+    result
+}
+
+/// Relative Strength Index using Wilder's smoothing.
+///
+/// Returns values in the 0..=100 range. If all changes are gains the RSI is
+/// 100; if all are losses the RSI is 0.
+pub fn calc_rsi_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
+    calc_rsi_smoothed_impl(data, period, SmoothingMethod::Wilder)
+}
+
+/// RSI with a selectable averaging of gains and losses.
+///
+/// All methods seed from the simple average of the first `period` changes
+/// and emit their first value at `data[period].ts`; they differ afterwards:
+///   - `Wilder`: avg = (prev * (period - 1) + current) / period
+///   - `Ema`:    avg = current * k + prev * (1 - k), k = 2 / (period + 1)
+///   - `Sma`:    plain average of the last `period` changes
+///
+/// `Sma` is Cutler's RSI: each value is recomputed from scratch over the
+/// trailing `period` changes, so value `j` depends only on
+/// `data[j..=j + period]`. As with the other methods the first value is
+/// stamped `data[period].ts` (the bar completing the first `period`
+/// changes) and the output has `data.len() - period` points.
+pub fn calc_rsi_smoothed_impl(
+    data: &[PricePoint],
+    period: usize,
+    method: SmoothingMethod,
+) -> Vec<IndicatorPoint> {
+    // `data.len() <= period` rather than `< period + 1`: a `usize::MAX`
+    // period coming from JS must not overflow the guard itself.
+    if period == 0 || data.len() <= period {
+        return Vec::new();
+    }
+
+    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(data.len().saturating_sub(period));
+
+    // Per-bar gains and losses; index i holds the change into data[i + 1].
+    let (gains, losses): (Vec<f64>, Vec<f64>) = data
+        .windows(2)
+        .map(|w| {
+            let change = w[1].close - w[0].close;
+            if change > 0.0 {
+                (change, 0.0)
+            } else {
+                (0.0, change.abs())
+            }
+        })
+        .unzip();
+
+    // Compute initial average gain / loss over the first `period` changes.
+    let mut avg_gain: f64 = gains[..period].iter().sum::<f64>() / period as f64;
+    let mut avg_loss: f64 = losses[..period].iter().sum::<f64>() / period as f64;
 
     result.push(IndicatorPoint {
         ts: data[period].ts,
-        value: rsi,
+        value: rsi_from_averages(avg_gain, avg_loss),
     });
 
-    // Subsequent values using Wilder's smoothing.
-    for i in (period + 1)..data.len() {
-        let change = data[i].close - data[i - 1].close;
-        let (gain, loss) = if change > 0.0 {
-            (change, 0.0)
-        } else {
-            (0.0, change.abs())
-        };
+    let n = period as f64;
+    let k = 2.0 / (n + 1.0);
 
-        avg_gain = (avg_gain * (period as f64 - 1.0) + gain) / period as f64;
-        avg_loss = (avg_loss * (period as f64 - 1.0) + loss) / period as f64;
+    // Running window sums for `Sma`. Floating-point subtraction leaves drift
+    // behind, so a window that holds no gains (or no losses) at all is snapped
+    // back to exactly zero: flat stretches still read 0 / 100.
+    let mut gain_sum: f64 = gains[..period].iter().sum();
+    let mut loss_sum: f64 = losses[..period].iter().sum();
+    let mut gain_count = gains[..period].iter().filter(|&&g| g > 0.0).count();
+    let mut loss_count = losses[..period].iter().filter(|&&l| l > 0.0).count();
 
-        let rsi = if avg_loss == 0.0 {
-            100.0
-        } else if avg_gain == 0.0 {
-            0.0
-        } else {
-            let rs = avg_gain / avg_loss;
-            100.0 - (100.0 / (1.0 + rs))
-        };
+    for i in period..gains.len() {
+        let (gain, loss) = (gains[i], losses[i]);
+
+        match method {
+            SmoothingMethod::Wilder => {
+                avg_gain = (avg_gain * (n - 1.0) + gain) / n;
+                avg_loss = (avg_loss * (n - 1.0) + loss) / n;
+            }
+            SmoothingMethod::Ema => {
+                avg_gain = gain * k + avg_gain * (1.0 - k);
+                avg_loss = loss * k + avg_loss * (1.0 - k);
+            }
+            SmoothingMethod::Sma => {
+                let (old_gain, old_loss) = (gains[i - period], losses[i - period]);
+                gain_sum += gain - old_gain;
+                loss_sum += loss - old_loss;
+                gain_count = gain_count + usize::from(gain > 0.0) - usize::from(old_gain > 0.0);
+                loss_count = loss_count + usize::from(loss > 0.0) - usize::from(old_loss > 0.0);
+                if gain_count == 0 {
+                    gain_sum = 0.0;
+                }
+                if loss_count == 0 {
+                    loss_sum = 0.0;
+                }
+                avg_gain = gain_sum / n;
+                avg_loss = loss_sum / n;
+            }
+        }
 
         result.push(IndicatorPoint {
-            ts: data[i].ts,
-            value: rsi,
+            ts: data[i + 1].ts,
+            value: rsi_from_averages(avg_gain, avg_loss),
         });
     }
 
     result
 }
 
+/// Map average gain / loss to the 0..=100 RSI scale.
+fn rsi_from_averages(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss == 0.0 {
+        100.0
+    } else if avg_gain == 0.0 {
+        0.0
+    } else {
+        let rs = avg_gain / avg_loss;
+        100.0 - (100.0 / (1.0 + rs))
+    }
+}
+
 /// Volume-Weighted Average Price.
 ///
 /// VWAP = cumulative(typical_price * volume) / cumulative(volume)
@@ -284,6 +1122,42 @@ pub fn calc_vwap_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
     result
 }
 
+/// Transpose columnar OHLCV arrays into row-oriented `PricePoint`s.
+///
+/// Every column must have the same length as `ts`; otherwise an error naming
+/// the offending column is returned.
+pub fn columnar_to_prices(cols: &OhlcvColumns) -> Result<Vec<PricePoint>, String> {
+    let len = cols.ts.len();
+    let columns = [
+        ("open", &cols.open),
+        ("high", &cols.high),
+        ("low", &cols.low),
+        ("close", &cols.close),
+        ("volume", &cols.volume),
+    ];
+    for (name, column) in columns {
+        if column.len() != len {
+            return Err(format!(
+                "column length mismatch: ts has {} values but {} has {}",
+                len,
+                name,
+                column.len()
+            ));
+        }
+    }
+
+    Ok((0..len)
+        .map(|i| PricePoint {
+            ts: cols.ts[i],
+            open: cols.open[i],
+            high: cols.high[i],
+            low: cols.low[i],
+            close: cols.close[i],
+            volume: cols.volume[i],
+        })
+        .collect())
+}
+
 /// Case-insensitive symbol / name search with relevance scoring.
 ///
 /// Scoring rules (highest applicable score wins per entry):
@@ -293,17 +1167,109 @@ pub fn calc_vwap_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
 ///   - Name starts with query   -> 40
 ///   - Name contains query      -> 20
 ///
-/// Results are sorted by score descending and capped at `max_results`.
+/// Within a tier, a bonus of up to 19 rewards queries that cover more of the
+/// matched field (the symbol for symbol tiers, the name for name tiers), so
+/// "AAP" ranks "AAPL" above "AAPLX". The bonus stays below the gap between
+/// tiers, so a higher tier always wins.
+///
+/// Results are sorted by tier descending, then by `rank` (unranked entries
+/// last), then by the coverage bonus, then by symbol, and capped at
+/// `max_results` (clamped to the number of entries; 0 returns nothing). A
+/// ranked entry therefore beats a closer but unranked match in its tier.
+///
+/// The query is trimmed first, so an all-whitespace query behaves like an
+/// empty one and returns the first `max_results` entries.
 pub fn filter_symbols_impl(
     entries: &[SymbolEntry],
     query: &str,
     max_results: usize,
 ) -> Vec<SymbolEntry> {
+    filter_symbols_counted_impl(entries, query, max_results).0
+}
+
+/// `filter_symbols_impl` plus the number of entries that matched before
+/// truncation to `max_results` (every entry, for an empty query).
+pub fn filter_symbols_counted_impl(
+    entries: &[SymbolEntry],
+    query: &str,
+    max_results: usize,
+) -> (Vec<SymbolEntry>, usize) {
+    search_symbols(entries, query, max_results, MatchMode::Anywhere, 0)
+}
+
+/// `filter_symbols_impl` with a choice of how name matches are counted.
+///
+/// In `MatchMode::WordBoundary` a name match only counts where it starts a
+/// word, and then scores like a name prefix (40) wherever the word sits, so
+/// "platforms" finds "Meta Platforms" but "art" no longer finds "Smart".
+pub fn filter_symbols_mode_impl(
+    entries: &[SymbolEntry],
+    query: &str,
+    max_results: usize,
+    mode: MatchMode,
+) -> Vec<SymbolEntry> {
+    search_symbols(entries, query, max_results, mode, 0).0
+}
+
+/// `filter_symbols_impl` keeping only entries scoring at least `min_score`,
+/// applied before truncation. Scores include the coverage bonus, so 40 keeps
+/// name-prefix matches but drops name-contains ones (20..=39), and 80
+/// requires a symbol prefix. An empty query still returns the first
+/// `max_results` entries.
+pub fn filter_symbols_min_impl(
+    entries: &[SymbolEntry],
+    query: &str,
+    max_results: usize,
+    min_score: i32,
+) -> Vec<SymbolEntry> {
+    search_symbols(entries, query, max_results, MatchMode::Anywhere, min_score).0
+}
+
+/// Whether `q` occurs in `name` at the start of a word.
+fn matches_word_start(name: &str, q: &str) -> bool {
+    name.match_indices(q).any(|(i, _)| {
+        name[..i]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric())
+    })
+}
+
+fn search_symbols(
+    entries: &[SymbolEntry],
+    query: &str,
+    max_results: usize,
+    mode: MatchMode,
+    min_score: i32,
+) -> (Vec<SymbolEntry>, usize) {
+    let max_results = max_results.min(entries.len());
+    let query = query.trim();
     if query.is_empty() {
-        return entries.iter().take(max_results).cloned().collect();
+        let page = entries.iter().take(max_results).cloned().collect();
+        return (page, entries.len());
     }
 
+    let mut scored = score_symbols(entries, query, mode);
+    scored.retain(|s| s.score >= min_score);
+    let total = scored.len();
+    scored.truncate(max_results);
+
+    let page = scored
+        .into_iter()
+        .map(|s| SymbolEntry {
+            symbol: s.symbol,
+            name: s.name,
+            rank: s.rank,
+        })
+        .collect();
+
+    (page, total)
+}
+
+/// Score every entry matching a non-empty `query` and sort the matches.
+fn score_symbols(entries: &[SymbolEntry], query: &str, mode: MatchMode) -> Vec<ScoredEntry> {
     let q = query.to_lowercase();
+    let q_len = q.chars().count();
 
     let mut scored: Vec<ScoredEntry> = Vec::new();
 
@@ -311,483 +1277,9146 @@ pub fn filter_symbols_impl(
         let sym = entry.symbol.to_lowercase();
         let name = entry.name.to_lowercase();
 
-        let score = if sym == q {
-            100
+        let (tier, field) = if sym == q {
+            (100, &sym)
         } else if sym.starts_with(&q) {
-            80
+            (80, &sym)
         } else if sym.contains(&q) {
-            60
+            (60, &sym)
         } else if name.starts_with(&q) {
-            40
+            (40, &name)
+        } else if mode == MatchMode::WordBoundary {
+            if matches_word_start(&name, &q) {
+                (40, &name)
+            } else {
+                continue;
+            }
         } else if name.contains(&q) {
-            20
+            (20, &name)
         } else {
             continue; // no match
         };
+        let score = tier + coverage_bonus(q_len, field.chars().count());
 
         scored.push(ScoredEntry {
             symbol: entry.symbol.clone(),
             name: entry.name.clone(),
+            rank: entry.rank,
+            tier,
             score,
         });
     }
 
-    // Sort descending by score, then alphabetically by symbol for stability.
-    scored.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.symbol.cmp(&b.symbol)));
-    scored.truncate(max_results);
-
+    scored.sort_by(cmp_scored);
     scored
-        .into_iter()
-        .map(|s| SymbolEntry {
-            symbol: s.symbol,
-            name: s.name,
-        })
-        .collect()
 }
 
-// ---------------------------------------------------------------------------
-// wasm_bindgen thin wrappers (JsValue <-> native types)
-// ---------------------------------------------------------------------------
-
-#[wasm_bindgen]
-pub fn lttb_downsample(data: JsValue, threshold: usize) -> JsValue {
-    let points: Vec<DataPoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
-    let result = lttb_downsample_impl(&points, threshold);
-    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+/// `query_len / field_len * 20`, capped at 19 so it never lifts an entry
+/// into the next tier.
+fn coverage_bonus(query_len: usize, field_len: usize) -> i32 {
+    if field_len == 0 {
+        return 0;
+    }
+    ((query_len as f32 / field_len as f32 * 20.0) as i32).min(19)
 }
 
-#[wasm_bindgen]
-pub fn calc_sma(data: JsValue, period: usize) -> JsValue {
-    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
-    let result = calc_sma_impl(&points, period);
-    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+/// Descending by tier, then by rank (unranked last), then descending by
+/// score (i.e. coverage bonus), then alphabetically by symbol for stability.
+fn cmp_scored(a: &ScoredEntry, b: &ScoredEntry) -> std::cmp::Ordering {
+    b.tier
+        .cmp(&a.tier)
+        .then_with(|| (a.rank.is_none(), a.rank).cmp(&(b.rank.is_none(), b.rank)))
+        .then_with(|| b.score.cmp(&a.score))
+        .then_with(|| a.symbol.cmp(&b.symbol))
 }
 
-#[wasm_bindgen]
-pub fn calc_ema(data: JsValue, period: usize) -> JsValue {
-    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
-    let result = calc_ema_impl(&points, period);
-    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
-}
+/// Search several named universes (e.g. equities and crypto) at once.
+///
+/// Each universe is scored with `filter_symbols_impl`'s rules, then the
+/// matches are merged, re-sorted in the same order and truncated to
+/// `max_results` overall, each tagged with the universe it came from.
+/// Entries that tie completely keep universe order. An empty query pages through the
+/// universes in order.
+pub fn filter_symbols_multi_impl(
+    universes: &[(String, Vec<SymbolEntry>)],
+    query: &str,
+    max_results: usize,
+) -> Vec<TaggedEntry> {
+    let tag = |source: &String, symbol: String, name: String, rank: Option<u32>| TaggedEntry {
+        symbol,
+        name,
+        rank,
+        source: source.clone(),
+    };
 
-#[wasm_bindgen]
-pub fn calc_rsi(data: JsValue, period: usize) -> JsValue {
-    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
-    let result = calc_rsi_impl(&points, period);
-    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
-}
+    let query = query.trim();
+    if query.is_empty() {
+        return universes
+            .iter()
+            .flat_map(|(source, entries)| entries.iter().map(move |e| (source, e)))
+            .take(max_results)
+            .map(|(source, e)| tag(source, e.symbol.clone(), e.name.clone(), e.rank))
+            .collect();
+    }
 
-#[wasm_bindgen]
-pub fn calc_vwap(data: JsValue) -> JsValue {
-    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
-    let result = calc_vwap_impl(&points);
-    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
-}
+    let mut merged: Vec<(&String, ScoredEntry)> = universes
+        .iter()
+        .flat_map(|(source, entries)| {
+            score_symbols(entries, query, MatchMode::Anywhere)
+                .into_iter()
+                .map(move |s| (source, s))
+        })
+        .collect();
+    merged.sort_by(|a, b| cmp_scored(&a.1, &b.1));
+    merged.truncate(max_results);
 
-#[wasm_bindgen]
-pub fn filter_symbols(entries: JsValue, query: JsValue, max_results: usize) -> JsValue {
-    let entries: Vec<SymbolEntry> = serde_wasm_bindgen::from_value(entries).unwrap_or_default();
-    let query: String = serde_wasm_bindgen::from_value(query).unwrap_or_default();
-    let result = filter_symbols_impl(&entries, &query, max_results);
-    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+    merged
+        .into_iter()
+        .map(|(source, s)| tag(source, s.symbol, s.name, s.rank))
+        .collect()
 }
 
-// ---------------------------------------------------------------------------
-// Unit tests (pure Rust — no JsValue required)
-// ---------------------------------------------------------------------------
+/// VWAP anchored at a user-picked bar.
+///
+/// Accumulation starts at the first bar whose `ts >= anchor_ts`; output only
+/// covers bars from the anchor onward and is empty if the anchor lies past
+/// the last bar.
+pub fn calc_vwap_from_impl(data: &[PricePoint], anchor_ts: f64) -> Vec<IndicatorPoint> {
+    match data.iter().position(|p| p.ts >= anchor_ts) {
+        Some(start) => calc_vwap_impl(&data[start..]),
+        None => Vec::new(),
+    }
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Roll consecutive bars up into a higher timeframe.
+///
+/// Every `factor` bars become one: open of the first, close of the last,
+/// max high, min low, summed volume, and the first bar's timestamp. A
+/// trailing partial group still produces a bar. `factor <= 1` returns the
+/// input unchanged.
+pub fn aggregate_timeframe_impl(data: &[PricePoint], factor: usize) -> Vec<PricePoint> {
+    if factor <= 1 {
+        return data.to_vec();
+    }
 
-    // -----------------------------------------------------------------------
-    // Helpers
-    // -----------------------------------------------------------------------
+    data.chunks(factor)
+        .map(|group| {
+            let first = &group[0];
+            let last = &group[group.len() - 1];
+            PricePoint {
+                ts: first.ts,
+                open: first.open,
+                high: group
+                    .iter()
+                    .map(|p| p.high)
+                    .fold(f64::NEG_INFINITY, f64::max),
+                low: group.iter().map(|p| p.low).fold(f64::INFINITY, f64::min),
+                close: last.close,
+                volume: group.iter().map(|p| p.volume).sum(),
+            }
+        })
+        .collect()
+}
 
-    fn dp(ts: f64, value: f64) -> DataPoint {
-        DataPoint { ts, value }
+/// Pivot-based swing detection.
+///
+/// A bar is a swing high if its high is strictly greater than the highs of
+/// the `left` bars before it and the `right` bars after it (swing lows
+/// mirror this on the low). Bars without a full window on both sides are
+/// never swings. Results are in bar order; a bar can be both.
+pub fn detect_swings_impl(data: &[PricePoint], left: usize, right: usize) -> Vec<Swing> {
+    let mut swings: Vec<Swing> = Vec::new();
+    if data.len() <= left.saturating_add(right) {
+        return swings;
     }
 
-    fn pp(ts: f64, open: f64, high: f64, low: f64, close: f64, volume: f64) -> PricePoint {
-        PricePoint {
-            ts,
-            open,
-            high,
-            low,
-            close,
-            volume,
+    for i in left..(data.len() - right) {
+        let neighbours = || {
+            data[i - left..=i + right]
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != left)
+        };
+        let p = &data[i];
+
+        if neighbours().all(|(_, q)| p.high > q.high) {
+            swings.push(Swing {
+                ts: p.ts,
+                index: i,
+                price: p.high,
+                kind: SwingKind::High,
+            });
+        }
+        if neighbours().all(|(_, q)| p.low < q.low) {
+            swings.push(Swing {
+                ts: p.ts,
+                index: i,
+                price: p.low,
+                kind: SwingKind::Low,
+            });
         }
     }
 
-    fn sample_prices() -> Vec<PricePoint> {
-        vec![
-            pp(1.0, 10.0, 12.0, 9.0, 11.0, 100.0),
-            pp(2.0, 11.0, 13.0, 10.0, 12.0, 150.0),
-            pp(3.0, 12.0, 14.0, 11.0, 13.0, 200.0),
-            pp(4.0, 13.0, 15.0, 12.0, 14.0, 120.0),
-            pp(5.0, 14.0, 16.0, 13.0, 15.0, 180.0),
-            pp(6.0, 15.0, 17.0, 14.0, 14.0, 160.0),
-            pp(7.0, 14.0, 15.0, 12.0, 13.0, 140.0),
-            pp(8.0, 13.0, 14.0, 11.0, 12.0, 130.0),
-            pp(9.0, 12.0, 13.0, 10.0, 11.0, 110.0),
-            pp(10.0, 11.0, 12.0, 9.0, 10.0, 100.0),
-        ]
-    }
+    swings
+}
 
-    // -----------------------------------------------------------------------
-    // LTTB downsampling
-    // -----------------------------------------------------------------------
+/// Group swing prices into horizontal support / resistance levels.
+///
+/// Swings are visited in ascending price order and join the current
+/// cluster while they sit within `tolerance_pct` percent (e.g. `0.5` for
+/// 0.5%) of its running mean. Each cluster reports its count-weighted mean
+/// price and number of touches; output is sorted by touches descending, then
+/// by price.
+pub fn cluster_levels_impl(swings: &[Swing], tolerance_pct: f64) -> Vec<LevelCluster> {
+    let mut prices: Vec<f64> = swings
+        .iter()
+        .map(|s| s.price)
+        .filter(|p| p.is_finite())
+        .collect();
+    prices.sort_by(|a, b| a.total_cmp(b));
 
-    #[test]
-    fn lttb_empty_input() {
-        let result = lttb_downsample_impl(&[], 5);
-        assert!(result.is_empty());
-    }
+    let tolerance = tolerance_pct.max(0.0) / 100.0;
+    let mut clusters: Vec<LevelCluster> = Vec::new();
+    let mut sum: f64 = 0.0;
 
-    #[test]
-    fn lttb_data_smaller_than_threshold() {
-        let data = vec![dp(1.0, 10.0), dp(2.0, 20.0)];
-        let result = lttb_downsample_impl(&data, 5);
-        assert_eq!(result.len(), 2);
-        assert_eq!(result[0].ts, 1.0);
-        assert_eq!(result[1].ts, 2.0);
+    for price in prices {
+        if let Some(current) = clusters.last_mut() {
+            if (price - current.price).abs() <= current.price.abs() * tolerance {
+                sum += price;
+                current.touches += 1;
+                current.price = sum / current.touches as f64;
+                continue;
+            }
+        }
+        sum = price;
+        clusters.push(LevelCluster { price, touches: 1 });
     }
 
-    #[test]
-    fn lttb_threshold_less_than_3() {
-        let data = vec![dp(1.0, 10.0), dp(2.0, 20.0), dp(3.0, 30.0)];
-        let result = lttb_downsample_impl(&data, 2);
-        assert_eq!(result.len(), 3, "threshold < 3 should return data as-is");
-    }
+    clusters.sort_by(|a, b| {
+        b.touches
+            .cmp(&a.touches)
+            .then_with(|| a.price.total_cmp(&b.price))
+    });
+    clusters
+}
 
-    #[test]
-    fn lttb_normal_downsample() {
-        let data: Vec<DataPoint> = (0..100).map(|i| dp(i as f64, (i as f64).sin())).collect();
-        let result = lttb_downsample_impl(&data, 20);
-        assert_eq!(result.len(), 20);
-        // First and last points must be preserved.
-        assert_eq!(result[0].ts, 0.0);
-        assert_eq!(result[19].ts, 99.0);
+/// Percent rank of each bar's value against the preceding `period` values.
+///
+/// Emits the percentage (0..=100) of the prior `period` values that are
+/// strictly less than the current one, timestamped at the current bar. The
+/// first output is at `data[period]`.
+pub fn calc_percent_rank_impl(
+    data: &[PricePoint],
+    period: usize,
+    source: PriceSource,
+) -> Vec<IndicatorPoint> {
+    if period == 0 || data.len() <= period {
+        return Vec::new();
     }
 
-    #[test]
-    fn lttb_threshold_of_3() {
-        let data: Vec<DataPoint> = (0..10).map(|i| dp(i as f64, (i * i) as f64)).collect();
-        let result = lttb_downsample_impl(&data, 3);
-        assert_eq!(result.len(), 3);
-        assert_eq!(result[0].ts, 0.0);
-        assert_eq!(result[2].ts, 9.0);
-    }
+    let values: Vec<f64> = data.iter().map(|p| source.value(p)).collect();
 
-    #[test]
-    fn lttb_data_equal_to_threshold() {
-        let data: Vec<DataPoint> = (0..5).map(|i| dp(i as f64, i as f64)).collect();
-        let result = lttb_downsample_impl(&data, 5);
-        assert_eq!(result.len(), 5);
+    (period..data.len())
+        .map(|i| {
+            let below = values[i - period..i]
+                .iter()
+                .filter(|v| **v < values[i])
+                .count();
+            IndicatorPoint {
+                ts: data[i].ts,
+                value: 100.0 * below as f64 / period as f64,
+            }
+        })
+        .collect()
+}
+
+/// Extreme of each trailing `period` window of `source` values, as
+/// `(ts, value, bars_ago)` aligned to the window's last bar. `better(a, b)`
+/// decides whether `a` replaces the current extreme `b`; ties resolve to the
+/// most recent bar.
+fn rolling_extreme(
+    data: &[PricePoint],
+    period: usize,
+    source: PriceSource,
+    better: fn(f64, f64) -> bool,
+) -> Vec<(f64, f64, usize)> {
+    if period == 0 || period > data.len() {
+        return Vec::new();
+    }
+
+    (period - 1..data.len())
+        .map(|end| {
+            let mut best_idx = end + 1 - period;
+            for j in best_idx + 1..=end {
+                let v = source.value(&data[j]);
+                let best = source.value(&data[best_idx]);
+                if v == best || better(v, best) {
+                    best_idx = j;
+                }
+            }
+            (data[end].ts, source.value(&data[best_idx]), end - best_idx)
+        })
+        .collect()
+}
+
+/// Highest `source` value over each trailing `period` bars.
+pub fn rolling_highest_impl(
+    data: &[PricePoint],
+    period: usize,
+    source: PriceSource,
+) -> Vec<IndicatorPoint> {
+    rolling_extreme(data, period, source, |a, b| a > b)
+        .into_iter()
+        .map(|(ts, value, _)| IndicatorPoint { ts, value })
+        .collect()
+}
+
+/// Lowest `source` value over each trailing `period` bars.
+pub fn rolling_lowest_impl(
+    data: &[PricePoint],
+    period: usize,
+    source: PriceSource,
+) -> Vec<IndicatorPoint> {
+    rolling_extreme(data, period, source, |a, b| a < b)
+        .into_iter()
+        .map(|(ts, value, _)| IndicatorPoint { ts, value })
+        .collect()
+}
+
+/// Bars since the highest `source` value within each trailing `period`
+/// window (0 = the current bar is the high).
+pub fn bars_since_highest_impl(
+    data: &[PricePoint],
+    period: usize,
+    source: PriceSource,
+) -> Vec<IndicatorPoint> {
+    rolling_extreme(data, period, source, |a, b| a > b)
+        .into_iter()
+        .map(|(ts, _, ago)| IndicatorPoint {
+            ts,
+            value: ago as f64,
+        })
+        .collect()
+}
+
+/// Bars since the lowest `source` value within each trailing `period`
+/// window (0 = the current bar is the low).
+pub fn bars_since_lowest_impl(
+    data: &[PricePoint],
+    period: usize,
+    source: PriceSource,
+) -> Vec<IndicatorPoint> {
+    rolling_extreme(data, period, source, |a, b| a < b)
+        .into_iter()
+        .map(|(ts, _, ago)| IndicatorPoint {
+            ts,
+            value: ago as f64,
+        })
+        .collect()
+}
+
+/// LTTB that guarantees every bar whose `ts` appears in `keep_ts` survives.
+///
+/// Runs normal LTTB, then for each kept timestamp replaces the point chosen
+/// for the bucket it falls in. If two kept timestamps share a bucket the
+/// second is inserted alongside, so the output may exceed `threshold` by the
+/// number of such collisions. Output stays sorted by ts; first and last
+/// points are always present. Timestamps not found in `data` are ignored.
+pub fn lttb_downsample_keep_impl(
+    data: &[DataPoint],
+    threshold: usize,
+    keep_ts: &[f64],
+) -> Vec<DataPoint> {
+    let len = data.len();
+    let mut selected = lttb_downsample_indices_impl(data, threshold);
+    if selected.len() == len {
+        return data.to_vec();
+    }
+
+    // Same bucket layout as `lttb_downsample_indices_impl`: bucket i covers
+    // [lttb_bucket_start(i), lttb_bucket_start(i + 1)) and its pick lives at
+    // selected[i + 1].
+    let bucket_size = (len - 2) as f64 / (threshold - 2) as f64;
+    let bucket_start = |i: usize| lttb_bucket_start(i, len, threshold);
+
+    let mut pinned = vec![false; selected.len()];
+    let mut extra: Vec<usize> = Vec::new();
+
+    for (k, p) in data.iter().enumerate().take(len - 1).skip(1) {
+        if !keep_ts.contains(&p.ts) {
+            continue;
+        }
+
+        let mut bucket = (((k - 1) as f64 / bucket_size) as usize).min(threshold - 3);
+        while bucket > 0 && bucket_start(bucket) > k {
+            bucket -= 1;
+        }
+        while bucket + 1 < threshold - 2 && bucket_start(bucket + 1) <= k {
+            bucket += 1;
+        }
+
+        let slot = bucket + 1;
+        if !pinned[slot] {
+            selected[slot] = k;
+            pinned[slot] = true;
+        } else {
+            extra.push(k);
+        }
+    }
+
+    selected.extend(extra);
+    selected.sort_unstable();
+    selected.dedup();
+    selected.into_iter().map(|i| data[i].clone()).collect()
+}
+
+/// Run a single batch spec. Unknown kinds produce an empty series.
+pub fn compute_indicator_impl(data: &[PricePoint], spec: &IndicatorSpec) -> Vec<IndicatorPoint> {
+    match spec.kind.as_str() {
+        "sma" => calc_sma_impl(data, spec.period),
+        "ema" => calc_ema_impl(data, spec.period),
+        "rsi" => calc_rsi_impl(data, spec.period),
+        "vwap" => calc_vwap_impl(data),
+        _ => Vec::new(),
+    }
+}
+
+/// Compute several indicators over the same bars in one call.
+pub fn compute_indicators_impl(
+    data: &[PricePoint],
+    specs: &[IndicatorSpec],
+) -> BTreeMap<String, Vec<IndicatorPoint>> {
+    specs
+        .iter()
+        .map(|spec| (spec.result_key(), compute_indicator_impl(data, spec)))
+        .collect()
+}
+
+/// Run one spec over many symbols' bars, returning one series per symbol in
+/// input order. With the `parallel` feature the symbols are spread across a
+/// rayon thread pool; the per-symbol math is the same either way.
+pub fn compute_many_impl(
+    symbols: &[Vec<PricePoint>],
+    spec: &IndicatorSpec,
+) -> Vec<Vec<IndicatorPoint>> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        symbols
+            .par_iter()
+            .map(|data| compute_indicator_impl(data, spec))
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        symbols
+            .iter()
+            .map(|data| compute_indicator_impl(data, spec))
+            .collect()
+    }
+}
+
+/// `compute_indicators_impl` with each indicator timed by `now_ms`, a
+/// millisecond clock. Kept separate so the untimed path never reads a clock.
+pub fn compute_indicators_timed_impl(
+    data: &[PricePoint],
+    specs: &[IndicatorSpec],
+    mut now_ms: impl FnMut() -> f64,
+) -> TimedIndicators {
+    let mut out = TimedIndicators::default();
+    for spec in specs {
+        let start = now_ms();
+        let points = compute_indicator_impl(data, spec);
+        let elapsed_ms = now_ms() - start;
+
+        let key = spec.result_key();
+        out.timings.insert(key.clone(), elapsed_ms * 1000.0);
+        out.results.insert(key, points);
+    }
+    out
+}
+
+/// Klinger Volume Oscillator (conventional periods 34 / 55 / 13).
+///
+/// Per bar from the second onward:
+///   - trend = +1 if high+low+close rose versus the prior bar, else -1
+///   - dm    = high - low
+///   - cm    = prev_cm + dm while the trend persists, prev_dm + dm on a flip
+///     (the first bar counts as a flip)
+///   - vf    = volume * |2 * dm / cm - 1| * trend * 100 (0 when cm is 0)
+///
+/// Both EMAs and the signal are SMA-seeded like `calc_ema_impl`.
+///
+/// KVO = EMA(vf, fast) - EMA(vf, slow) starting where both EMAs exist, and
+/// the signal is an EMA of KVO over `signal` bars.
+pub fn calc_klinger_impl(
+    data: &[PricePoint],
+    fast: usize,
+    slow: usize,
+    signal: usize,
+) -> KlingerResult {
+    let longest = fast.max(slow);
+    if fast == 0 || slow == 0 || signal == 0 || data.len() <= longest {
+        return KlingerResult::default();
+    }
+
+    // Volume force, one value per bar from data[1].
+    let mut vf: Vec<f64> = Vec::with_capacity(data.len() - 1);
+    let mut prev_trend: f64 = 0.0;
+    let mut prev_dm: f64 = data[0].high - data[0].low;
+    let mut cm: f64 = prev_dm;
+
+    for w in data.windows(2) {
+        let (prev, cur) = (&w[0], &w[1]);
+        let trend = if cur.high + cur.low + cur.close > prev.high + prev.low + prev.close {
+            1.0
+        } else {
+            -1.0
+        };
+        let dm = cur.high - cur.low;
+        cm = if trend == prev_trend {
+            cm + dm
+        } else {
+            prev_dm + dm
+        };
+
+        let force = if cm == 0.0 {
+            0.0
+        } else {
+            cur.volume * (2.0 * (dm / cm) - 1.0).abs() * trend * 100.0
+        };
+        vf.push(force);
+
+        prev_trend = trend;
+        prev_dm = dm;
+    }
+
+    let fast_ema = ema_over_values(&vf, fast);
+    let slow_ema = ema_over_values(&vf, slow);
+
+    // vf[t] belongs to data[t + 1].
+    let mut kvo: Vec<IndicatorPoint> = Vec::with_capacity(vf.len() + 1 - longest);
+    for t in (longest - 1)..vf.len() {
+        kvo.push(IndicatorPoint {
+            ts: data[t + 1].ts,
+            value: fast_ema[t + 1 - fast] - slow_ema[t + 1 - slow],
+        });
+    }
+
+    let kvo_values: Vec<f64> = kvo.iter().map(|p| p.value).collect();
+    let signal_line = ema_over_values(&kvo_values, signal)
+        .into_iter()
+        .enumerate()
+        .map(|(j, value)| IndicatorPoint {
+            ts: kvo[signal - 1 + j].ts,
+            value,
+        })
+        .collect();
+
+    KlingerResult {
+        kvo,
+        signal: signal_line,
+    }
+}
+
+/// Ehlers' Fisher Transform of median price.
+///
+/// Over each trailing `period` window the median price (high + low) / 2 is
+/// normalised to -1..1, smoothed as `0.66 * x + 0.67 * prev` and clamped to
+/// ±0.999 so the log stays finite, then transformed with
+/// `0.5 * ln((1 + x) / (1 - x)) + 0.5 * prev_fisher`. Fisher values start at
+/// `data[period - 1]`; the trigger is the fisher line delayed by one bar, so
+/// it starts one bar later. A flat window normalises to 0.
+pub fn calc_fisher_impl(data: &[PricePoint], period: usize) -> FisherResult {
+    if period == 0 || period > data.len() {
+        return FisherResult::default();
+    }
+
+    let medians: Vec<f64> = data.iter().map(|p| PriceSource::Median.value(p)).collect();
+    let mut fisher: Vec<IndicatorPoint> = Vec::with_capacity(data.len() - period + 1);
+    let mut prev_value: f64 = 0.0;
+    let mut prev_fisher: f64 = 0.0;
+
+    for end in (period - 1)..data.len() {
+        let window = &medians[end + 1 - period..=end];
+        let max = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let min = window.iter().copied().fold(f64::INFINITY, f64::min);
+
+        let position = if max > min {
+            (medians[end] - min) / (max - min) - 0.5
+        } else {
+            0.0
+        };
+        let value = (0.66 * position + 0.67 * prev_value).clamp(-0.999, 0.999);
+        let f = 0.5 * ((1.0 + value) / (1.0 - value)).ln() + 0.5 * prev_fisher;
+
+        fisher.push(IndicatorPoint {
+            ts: data[end].ts,
+            value: f,
+        });
+        prev_value = value;
+        prev_fisher = f;
+    }
+
+    let trigger = fisher
+        .windows(2)
+        .map(|w| IndicatorPoint {
+            ts: w[1].ts,
+            value: w[0].value,
+        })
+        .collect();
+
+    FisherResult { fisher, trigger }
+}
+
+/// Absolute size of the candle body, `|close - open|`.
+pub fn body_size(p: &PricePoint) -> f64 {
+    (p.close - p.open).abs()
+}
+
+/// Distance from the top of the body to the high.
+pub fn upper_wick(p: &PricePoint) -> f64 {
+    p.high - p.open.max(p.close)
+}
+
+/// Distance from the bottom of the body to the low.
+pub fn lower_wick(p: &PricePoint) -> f64 {
+    p.open.min(p.close) - p.low
+}
+
+/// Balance of Power: `(close - open) / (high - low)` per bar, 0.0 for a
+/// zero-range bar.
+///
+/// With `smoothing <= 1` one value is emitted per bar; otherwise the raw
+/// series is passed through an SMA of that length and aligned to each
+/// window's last bar.
+pub fn calc_bop_impl(data: &[PricePoint], smoothing: usize) -> Vec<IndicatorPoint> {
+    let raw: Vec<f64> = data
+        .iter()
+        .map(|p| {
+            let range = p.high - p.low;
+            if range == 0.0 {
+                0.0
+            } else {
+                (p.close - p.open) / range
+            }
+        })
+        .collect();
+
+    if smoothing <= 1 {
+        return data
+            .iter()
+            .zip(raw)
+            .map(|(p, value)| IndicatorPoint { ts: p.ts, value })
+            .collect();
+    }
+
+    sma_over_values(&raw, smoothing)
+        .into_iter()
+        .enumerate()
+        .map(|(j, value)| IndicatorPoint {
+            ts: data[smoothing - 1 + j].ts,
+            value,
+        })
+        .collect()
+}
+
+/// Candlestick patterns with default thresholds.
+pub fn detect_patterns_impl(data: &[PricePoint]) -> Vec<PatternHit> {
+    detect_patterns_with_impl(data, &PatternThresholds::default())
+}
+
+/// Flag doji, hammer, shooting star and bullish / bearish engulfing bars.
+///
+/// Single-bar patterns are judged on body and wick sizes relative to the
+/// bar's range (zero-range bars never match). Engulfing requires the current
+/// body to fully cover the prior, opposite-coloured body. Hits are in
+/// timestamp order; one bar can produce several.
+pub fn detect_patterns_with_impl(
+    data: &[PricePoint],
+    thresholds: &PatternThresholds,
+) -> Vec<PatternHit> {
+    let mut hits: Vec<PatternHit> = Vec::new();
+
+    for (i, p) in data.iter().enumerate() {
+        let range = p.high - p.low;
+        if range <= 0.0 {
+            continue;
+        }
+        let body = body_size(p);
+        let upper = upper_wick(p);
+        let lower = lower_wick(p);
+        let mut hit = |pattern| hits.push(PatternHit { ts: p.ts, pattern });
+
+        if body / range <= thresholds.doji_body_ratio {
+            hit(PatternKind::Doji);
+        }
+
+        let small_body = body / range <= thresholds.hammer_body_ratio;
+        if small_body
+            && lower >= thresholds.hammer_wick_ratio * body
+            && upper / range <= thresholds.hammer_opposite_wick_ratio
+        {
+            hit(PatternKind::Hammer);
+        }
+        if small_body
+            && upper >= thresholds.hammer_wick_ratio * body
+            && lower / range <= thresholds.hammer_opposite_wick_ratio
+        {
+            hit(PatternKind::ShootingStar);
+        }
+
+        if i > 0 {
+            let prev = &data[i - 1];
+            let engulfs = p.open.min(p.close) <= prev.open.min(prev.close)
+                && p.open.max(p.close) >= prev.open.max(prev.close)
+                && body > body_size(prev);
+            if engulfs && prev.close < prev.open && p.close > p.open {
+                hit(PatternKind::BullishEngulfing);
+            }
+            if engulfs && prev.close > prev.open && p.close < p.open {
+                hit(PatternKind::BearishEngulfing);
+            }
+        }
+    }
+
+    hits
+}
+
+/// Pine-style `nz`: `value` unless it is NaN, otherwise `default`.
+pub fn nz(value: f64, default: f64) -> f64 {
+    if value.is_nan() {
+        default
+    } else {
+        value
+    }
+}
+
+/// Pine-style `change`: `values[i] - values[i - n]`, NaN for the first `n`
+/// bars. Output has the same length as the input.
+pub fn change_impl(values: &[f64], n: usize) -> Vec<f64> {
+    (0..values.len())
+        .map(|i| {
+            if i >= n {
+                values[i] - values[i - n]
+            } else {
+                f64::NAN
+            }
+        })
+        .collect()
+}
+
+/// Pine-style `valuewhen`: at each bar, the value at the `occurrence`-th most
+/// recent bar (0 = latest, including the current bar) where the condition
+/// held; NaN until enough conditions have been seen. Inputs are truncated to
+/// the shorter length.
+pub fn valuewhen_impl(conditions: &[bool], values: &[f64], occurrence: usize) -> Vec<f64> {
+    let len = conditions.len().min(values.len());
+    let mut hits: Vec<f64> = Vec::new();
+
+    (0..len)
+        .map(|i| {
+            if conditions[i] {
+                hits.push(values[i]);
+            }
+            if occurrence < hits.len() {
+                hits[hits.len() - 1 - occurrence]
+            } else {
+                f64::NAN
+            }
+        })
+        .collect()
+}
+
+/// Ultimate Oscillator (conventional periods 7 / 14 / 28).
+///
+/// From the second bar, buying pressure is `close - min(low, prev_close)`
+/// and true range `max(high, prev_close) - min(low, prev_close)`. Each
+/// period's average is sum(BP) / sum(TR) over its trailing window (0.0 when
+/// the TR sum is zero), combined as
+/// `100 * (4 * short + 2 * medium + long) / 7`. The first value is at
+/// `data[max(short, medium, long)]`.
+pub fn calc_ultimate_impl(
+    data: &[PricePoint],
+    short: usize,
+    medium: usize,
+    long: usize,
+) -> Vec<IndicatorPoint> {
+    let longest = short.max(medium).max(long);
+    if short == 0 || medium == 0 || long == 0 || data.len() <= longest {
+        return Vec::new();
+    }
+
+    // bp[t] / tr[t] belong to data[t + 1].
+    let (bp, tr): (Vec<f64>, Vec<f64>) = data
+        .windows(2)
+        .map(|w| {
+            let floor = w[1].low.min(w[0].close);
+            let ceiling = w[1].high.max(w[0].close);
+            (w[1].close - floor, ceiling - floor)
+        })
+        .unzip();
+
+    let average = |end: usize, period: usize| {
+        let window = end + 1 - period..=end;
+        let tr_sum: f64 = tr[window.clone()].iter().sum();
+        if tr_sum == 0.0 {
+            0.0
+        } else {
+            bp[window].iter().sum::<f64>() / tr_sum
+        }
+    };
+
+    ((longest - 1)..bp.len())
+        .map(|t| IndicatorPoint {
+            ts: data[t + 1].ts,
+            value: 100.0 * (4.0 * average(t, short) + 2.0 * average(t, medium) + average(t, long))
+                / 7.0,
+        })
+        .collect()
+}
+
+/// Mass Index (conventional periods 9 and 25).
+///
+/// ratio = EMA(high - low) / EMA(EMA(high - low)), summed over the trailing
+/// `sum_period` ratios. A ratio with a zero denominator (a flat, zero-range
+/// stretch) counts as 1.0. The first value is at
+/// `data[2 * (ema_period - 1) + sum_period - 1]`; readings rising above ~27
+/// and falling back below ~26.5 form the "reversal bulge".
+pub fn calc_mass_index_impl(
+    data: &[PricePoint],
+    ema_period: usize,
+    sum_period: usize,
+) -> Vec<IndicatorPoint> {
+    if ema_period == 0 || sum_period == 0 {
+        return Vec::new();
+    }
+
+    let ranges: Vec<f64> = data.iter().map(|p| p.high - p.low).collect();
+    let single = ema_over_values(&ranges, ema_period);
+    let double = ema_over_values(&single, ema_period);
+
+    // double[j] lines up with single[ema_period - 1 + j] and with
+    // data[2 * (ema_period - 1) + j].
+    let ratios: Vec<f64> = double
+        .iter()
+        .enumerate()
+        .map(|(j, d)| {
+            if *d == 0.0 {
+                1.0
+            } else {
+                single[ema_period - 1 + j] / d
+            }
+        })
+        .collect();
+
+    if ratios.len() < sum_period {
+        return Vec::new();
+    }
+
+    let offset = 2 * (ema_period - 1);
+    let mut window_sum: f64 = ratios[..sum_period].iter().sum();
+    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(ratios.len() - sum_period + 1);
+    result.push(IndicatorPoint {
+        ts: data[offset + sum_period - 1].ts,
+        value: window_sum,
+    });
+    for j in sum_period..ratios.len() {
+        window_sum += ratios[j] - ratios[j - sum_period];
+        result.push(IndicatorPoint {
+            ts: data[offset + j].ts,
+            value: window_sum,
+        });
+    }
+
+    result
+}
+
+/// Shared NVI / PVI walk: starts at 1000 and applies the bar's percentage
+/// close change only when `volume_moved(prev_volume, volume)` holds. A zero
+/// prior close leaves the index unchanged.
+fn volume_index(data: &[PricePoint], volume_moved: fn(f64, f64) -> bool) -> Vec<IndicatorPoint> {
+    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(data.len());
+    let mut index: f64 = 1000.0;
+
+    for (i, p) in data.iter().enumerate() {
+        if i > 0 {
+            let prev = &data[i - 1];
+            if volume_moved(prev.volume, p.volume) && prev.close != 0.0 {
+                index *= 1.0 + (p.close - prev.close) / prev.close;
+            }
+        }
+        result.push(IndicatorPoint {
+            ts: p.ts,
+            value: index,
+        });
+    }
+
+    result
+}
+
+/// Negative Volume Index: moves only on bars where volume fell.
+pub fn calc_nvi_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
+    volume_index(data, |prev, cur| cur < prev)
+}
+
+/// Positive Volume Index: moves only on bars where volume rose.
+pub fn calc_pvi_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
+    volume_index(data, |prev, cur| cur > prev)
+}
+
+/// Accumulation / Distribution Line.
+///
+/// Running sum of money-flow volume, where the multiplier is
+/// `((close - low) - (high - close)) / (high - low)` (0.0 for a zero-range
+/// bar). One value per bar.
+pub fn calc_adl_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
+    let mut adl: f64 = 0.0;
+    data.iter()
+        .map(|p| {
+            let range = p.high - p.low;
+            if range != 0.0 {
+                adl += ((p.close - p.low) - (p.high - p.close)) / range * p.volume;
+            }
+            IndicatorPoint {
+                ts: p.ts,
+                value: adl,
+            }
+        })
+        .collect()
+}
+
+/// Chaikin Oscillator (conventional periods 3 and 10):
+/// `EMA(ADL, fast) - EMA(ADL, slow)`, starting at `data[slow - 1]` where both
+/// EMAs exist. Empty unless `0 < fast < slow <= data.len()`.
+pub fn calc_chaikin_osc_impl(data: &[PricePoint], fast: usize, slow: usize) -> Vec<IndicatorPoint> {
+    if fast == 0 || fast >= slow || slow > data.len() {
+        return Vec::new();
+    }
+
+    let adl: Vec<f64> = calc_adl_impl(data).into_iter().map(|p| p.value).collect();
+    let fast_ema = ema_over_values(&adl, fast);
+    let slow_ema = ema_over_values(&adl, slow);
+
+    slow_ema
+        .iter()
+        .enumerate()
+        .map(|(j, slow_value)| IndicatorPoint {
+            ts: data[slow - 1 + j].ts,
+            value: fast_ema[slow - fast + j] - slow_value,
+        })
+        .collect()
+}
+
+/// Elder Ray: bull power `high - EMA(close)` and bear power
+/// `low - EMA(close)`, aligned to where the EMA is defined.
+pub fn calc_elder_ray_impl(data: &[PricePoint], period: usize) -> ElderRayResult {
+    let ema = calc_ema_impl(data, period);
+    if ema.is_empty() {
+        return ElderRayResult::default();
+    }
+
+    let bars = &data[period - 1..];
+    ElderRayResult {
+        bull_power: bars
+            .iter()
+            .zip(&ema)
+            .map(|(p, e)| IndicatorPoint {
+                ts: e.ts,
+                value: p.high - e.value,
+            })
+            .collect(),
+        bear_power: bars
+            .iter()
+            .zip(&ema)
+            .map(|(p, e)| IndicatorPoint {
+                ts: e.ts,
+                value: p.low - e.value,
+            })
+            .collect(),
+    }
+}
+
+/// Epoch values below this are treated as seconds: 1e11 s is the year 5138,
+/// while 1e11 ms is only March 1973.
+const SECONDS_CUTOFF: f64 = 1e11;
+
+/// Guess whether bar timestamps are epoch seconds or milliseconds from the
+/// largest magnitude present. Empty input reports `Millis`.
+pub fn detect_ts_unit(data: &[PricePoint]) -> TsUnit {
+    let max = data.iter().map(|p| p.ts.abs()).fold(0.0, f64::max);
+    if max > 0.0 && max < SECONDS_CUTOFF {
+        TsUnit::Seconds
+    } else {
+        TsUnit::Millis
+    }
+}
+
+/// Multiply every timestamp by `factor` in place.
+pub fn scale_timestamps_impl(data: &mut [PricePoint], factor: f64) {
+    for p in data.iter_mut() {
+        p.ts *= factor;
+    }
+}
+
+/// Convert timestamps to milliseconds (`to_millis`) or seconds, using
+/// `detect_ts_unit` to decide whether anything needs to change.
+pub fn normalize_ts_impl(data: &mut [PricePoint], to_millis: bool) {
+    match (detect_ts_unit(data), to_millis) {
+        (TsUnit::Seconds, true) => scale_timestamps_impl(data, 1000.0),
+        (TsUnit::Millis, false) => scale_timestamps_impl(data, 0.001),
+        _ => {}
+    }
+}
+
+/// Cumulative VWAP that omits the leading bars before any volume has
+/// traded (e.g. quiet pre-market), instead of plotting them at 0.0.
+pub fn calc_vwap_skip_zero_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
+    let mut cum_vol: f64 = 0.0;
+    let start = data.iter().position(|p| {
+        cum_vol += p.volume;
+        cum_vol > 0.0
+    });
+
+    match start {
+        Some(start) => calc_vwap_impl(data).split_off(start),
+        None => Vec::new(),
+    }
+}
+
+/// SMA restricted to the bars in `[start_idx, end_idx)`.
+///
+/// Bars before `start_idx` still fill the initial window, so the first
+/// visible value equals the full-series SMA at that bar; bars in the range
+/// that lack a full window (index < period - 1) are omitted. `end_idx` is
+/// clamped to the data length.
+pub fn calc_sma_range_impl(
+    data: &[PricePoint],
+    period: usize,
+    start_idx: usize,
+    end_idx: usize,
+) -> Vec<IndicatorPoint> {
+    let end_idx = end_idx.min(data.len());
+    if period == 0 || start_idx >= end_idx {
+        return Vec::new();
+    }
+
+    // Only the bars that can influence the visible range are touched.
+    let first_bar = start_idx.max(period - 1);
+    if first_bar >= end_idx {
+        return Vec::new();
+    }
+    calc_sma_impl(&data[first_bar + 1 - period..end_idx], period)
+}
+
+/// Report every place consecutive bars are further apart than
+/// `expected_interval_ms * (1 + tolerance)`.
+///
+/// `start_ts` / `end_ts` are the bars either side of the hole and
+/// `missing_bars` the estimated number of absent bars (at least 1).
+/// Read-only; a non-positive interval yields no gaps.
+pub fn find_gaps_impl(data: &[PricePoint], expected_interval_ms: f64, tolerance: f64) -> Vec<Gap> {
+    if expected_interval_ms.is_nan() || expected_interval_ms <= 0.0 {
+        return Vec::new();
+    }
+
+    let limit = expected_interval_ms * (1.0 + tolerance.max(0.0));
+    data.windows(2)
+        .filter(|w| w[1].ts - w[0].ts > limit)
+        .map(|w| {
+            let spacing = w[1].ts - w[0].ts;
+            let missing = (spacing / expected_interval_ms).round() as usize;
+            Gap {
+                start_ts: w[0].ts,
+                end_ts: w[1].ts,
+                missing_bars: missing.saturating_sub(1).max(1),
+            }
+        })
+        .collect()
+}
+
+/// Closes of the up-to-`window` bars surrounding `i` (excluding `i`),
+/// centred where possible and shifted inward at the edges.
+fn neighbour_closes(data: &[PricePoint], i: usize, window: usize) -> Vec<f64> {
+    let span = window.min(data.len() - 1);
+    let start = i.saturating_sub(span / 2).min(data.len() - 1 - span);
+    (start..=start + span)
+        .filter(|j| *j != i)
+        .map(|j| data[j].close)
+        .collect()
+}
+
+/// Indices of bars whose close lies more than `z_threshold` standard
+/// deviations from the mean of the `window` surrounding closes (the bar
+/// itself excluded, so a spike cannot mask itself). When the neighbours are
+/// all equal any deviation counts as an outlier.
+pub fn detect_outliers_impl(data: &[PricePoint], window: usize, z_threshold: f64) -> Vec<usize> {
+    if window < 2 || data.len() < 3 {
+        return Vec::new();
+    }
+
+    (0..data.len())
+        .filter(|&i| {
+            let neighbours = neighbour_closes(data, i, window);
+            let n = neighbours.len() as f64;
+            let mean = neighbours.iter().sum::<f64>() / n;
+            let var = neighbours.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+            let deviation = (data[i].close - mean).abs();
+            if var == 0.0 {
+                deviation > 0.0
+            } else {
+                deviation / var.sqrt() > z_threshold
+            }
+        })
+        .collect()
+}
+
+/// Copy of `data` with each outlier's close replaced by the median of its
+/// surrounding closes (see `detect_outliers_impl`).
+pub fn clean_outliers_impl(
+    data: &[PricePoint],
+    window: usize,
+    z_threshold: f64,
+) -> Vec<PricePoint> {
+    let mut cleaned = data.to_vec();
+    for i in detect_outliers_impl(data, window, z_threshold) {
+        let mut neighbours = neighbour_closes(data, i, window);
+        neighbours.sort_by(|a, b| a.total_cmp(b));
+        let mid = neighbours.len() / 2;
+        cleaned[i].close = if neighbours.len().is_multiple_of(2) {
+            (neighbours[mid - 1] + neighbours[mid]) / 2.0
+        } else {
+            neighbours[mid]
+        };
+    }
+    cleaned
+}
+
+/// EMA with a selectable seed. `SmaWindow` is identical to `calc_ema_impl`;
+/// `FirstValue` starts from the first close and emits one point per bar.
+pub fn calc_ema_seeded_impl(
+    data: &[PricePoint],
+    period: usize,
+    seed: EmaSeed,
+) -> Vec<IndicatorPoint> {
+    match seed {
+        EmaSeed::SmaWindow => calc_ema_impl(data, period),
+        EmaSeed::FirstValue => {
+            if period == 0 {
+                return Vec::new();
+            }
+            ema_from_first_close(data, 2.0 / (period + 1) as f64)
+        }
+    }
+}
+
+/// EMA with smoothing factor `k`, seeded with the first close; one point per
+/// bar.
+fn ema_from_first_close(data: &[PricePoint], k: f64) -> Vec<IndicatorPoint> {
+    let Some(first) = data.first() else {
+        return Vec::new();
+    };
+    let mut prev_ema = first.close;
+    data.iter()
+        .map(|p| {
+            prev_ema = p.close * k + prev_ema * (1.0 - k);
+            IndicatorPoint {
+                ts: p.ts,
+                value: prev_ema,
+            }
+        })
+        .collect()
+}
+
+/// EMA parameterized by half-life: a bar's weight halves every `half_life`
+/// bars, i.e. `k = 1 - 0.5^(1 / half_life)`. Seeded with the first close
+/// like `EmaSeed::FirstValue`, so it emits one point per bar. Empty for a
+/// non-positive or non-finite half-life.
+pub fn calc_ema_halflife_impl(data: &[PricePoint], half_life: f64) -> Vec<IndicatorPoint> {
+    if !half_life.is_finite() || half_life <= 0.0 {
+        return Vec::new();
+    }
+    ema_from_first_close(data, 1.0 - 0.5_f64.powf(1.0 / half_life))
+}
+
+/// Percentage distance of the close from its moving average:
+/// `100 * (close - ma) / ma`. Aligned to where the MA is defined, one point
+/// per MA bar; a bar whose MA is zero gets `NaN` rather than being dropped,
+/// so the series stays index-aligned with the MA.
+pub fn calc_ma_distance_impl(
+    data: &[PricePoint],
+    period: usize,
+    ma_kind: MaKind,
+) -> Vec<IndicatorPoint> {
+    let ma = ma_kind.compute(data, period);
+    if ma.is_empty() {
+        return Vec::new();
+    }
+
+    data[period - 1..]
+        .iter()
+        .zip(&ma)
+        .map(|(p, m)| IndicatorPoint {
+            ts: p.ts,
+            value: if m.value == 0.0 {
+                f64::NAN
+            } else {
+                100.0 * (p.close - m.value) / m.value
+            },
+        })
+        .collect()
+}
+
+/// VWAP over a trailing `period`-bar window, timestamped at the window's
+/// last bar. A window with no volume falls back to its mean typical price.
+pub fn calc_rolling_vwap_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
+    if period == 0 || period > data.len() {
+        return Vec::new();
+    }
+
+    data.windows(period)
+        .map(|window| {
+            let mut tp_sum: f64 = 0.0;
+            let mut tp_vol: f64 = 0.0;
+            let mut vol: f64 = 0.0;
+            for p in window {
+                let typical_price = (p.high + p.low + p.close) / 3.0;
+                tp_sum += typical_price;
+                tp_vol += typical_price * p.volume;
+                vol += p.volume;
+            }
+
+            let value = if vol == 0.0 {
+                tp_sum / period as f64
+            } else {
+                tp_vol / vol
+            };
+            IndicatorPoint {
+                ts: window[period - 1].ts,
+                value,
+            }
+        })
+        .collect()
+}
+
+// --- Expression evaluator --------------------------------------------------
+//
+// Grammar:
+//   expr    := term (('+' | '-') term)*
+//   term    := unary (('*' | '/') unary)*
+//   unary   := '-' unary | primary
+//   primary := number | series | func '(' expr ',' number ')' | '(' expr ')'
+//
+// Series are `open`, `high`, `low`, `close`, `volume`; functions are `sma`
+// and `ema`. Values are evaluated per bar as `Option<f64>`, `None` marking
+// bars where a window is not yet full or a division hit zero.
+
+/// Upper bound on operators, calls and parenthesised groups in one
+/// expression. Each of them adds at most one level to both the parser's
+/// recursion and the AST, so this caps recursion depth for input from JS.
+const MAX_EXPR_NODES: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExprToken {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Series(PriceSource),
+    Call(String, Box<Expr>, usize),
+    Neg(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+fn tokenize_expr(src: &str) -> Result<Vec<ExprToken>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{}'", text))?;
+            tokens.push(ExprToken::Number(n));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            tokens.push(ExprToken::Ident(ident.to_ascii_lowercase()));
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' => ExprToken::Op(c),
+                '(' => ExprToken::LParen,
+                ')' => ExprToken::RParen,
+                ',' => ExprToken::Comma,
+                _ => return Err(format!("unexpected character '{}'", c)),
+            });
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+struct ExprParser {
+    tokens: Vec<ExprToken>,
+    pos: usize,
+    nodes: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&ExprToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<ExprToken> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, want: ExprToken, what: &str) -> Result<(), String> {
+        match self.next() {
+            Some(ref t) if *t == want => Ok(()),
+            _ => Err(format!("expected {}", what)),
+        }
+    }
+
+    fn grow(&mut self) -> Result<(), String> {
+        self.nodes += 1;
+        if self.nodes > MAX_EXPR_NODES {
+            return Err(format!(
+                "expression too deeply nested (limit {} operations)",
+                MAX_EXPR_NODES
+            ));
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(&ExprToken::Op(op @ ('+' | '-'))) = self.peek() {
+            self.pos += 1;
+            self.grow()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while let Some(&ExprToken::Op(op @ ('*' | '/'))) = self.peek() {
+            self.pos += 1;
+            self.grow()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&ExprToken::Op('-')) {
+            self.pos += 1;
+            self.grow()?;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(ExprToken::Number(n)) => Ok(Expr::Number(n)),
+            Some(ExprToken::LParen) => {
+                self.grow()?;
+                let inner = self.expr()?;
+                self.expect(ExprToken::RParen, "')'")?;
+                Ok(inner)
+            }
+            Some(ExprToken::Ident(name)) => {
+                if self.peek() != Some(&ExprToken::LParen) {
+                    return match name.as_str() {
+                        "open" => Ok(Expr::Series(PriceSource::Open)),
+                        "high" => Ok(Expr::Series(PriceSource::High)),
+                        "low" => Ok(Expr::Series(PriceSource::Low)),
+                        "close" => Ok(Expr::Series(PriceSource::Close)),
+                        "volume" => Ok(Expr::Series(PriceSource::Volume)),
+                        _ => Err(format!("unknown series '{}'", name)),
+                    };
+                }
+                if !matches!(name.as_str(), "sma" | "ema") {
+                    return Err(format!("unknown function '{}'", name));
+                }
+                self.pos += 1;
+                self.grow()?;
+                let arg = self.expr()?;
+                self.expect(ExprToken::Comma, "',' before period")?;
+                let period = match self.next() {
+                    Some(ExprToken::Number(n)) if n >= 1.0 && n.fract() == 0.0 => n as usize,
+                    _ => return Err(format!("{} period must be a positive integer", name)),
+                };
+                self.expect(ExprToken::RParen, "')'")?;
+                Ok(Expr::Call(name, Box::new(arg), period))
+            }
+            Some(token) => Err(format!("unexpected token {:?}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Rolling mean over `values`; defined only where the whole window is.
+fn sma_over_options(values: &[Option<f64>], period: usize) -> Vec<Option<f64>> {
+    (0..values.len())
+        .map(|i| {
+            if i + 1 < period {
+                return None;
+            }
+            let window = &values[i + 1 - period..=i];
+            let sum: Option<f64> = window.iter().copied().sum();
+            sum.map(|s| s / period as f64)
+        })
+        .collect()
+}
+
+/// EMA over `values`, SMA-seeded after `period` consecutive defined values
+/// and re-seeded after any gap.
+fn ema_over_options(values: &[Option<f64>], period: usize) -> Vec<Option<f64>> {
+    let k: f64 = 2.0 / period.saturating_add(1) as f64;
+    let seeds = sma_over_options(values, period);
+    let mut prev: Option<f64> = None;
+    values
+        .iter()
+        .zip(seeds)
+        .map(|(v, seed)| {
+            prev = match (v, prev) {
+                (Some(v), Some(p)) => Some(v * k + p * (1.0 - k)),
+                (Some(_), None) => seed,
+                (None, _) => None,
+            };
+            prev
+        })
+        .collect()
+}
+
+fn eval_expr(expr: &Expr, data: &[PricePoint]) -> Vec<Option<f64>> {
+    match expr {
+        Expr::Number(n) => vec![Some(*n); data.len()],
+        Expr::Series(source) => data.iter().map(|p| Some(source.value(p))).collect(),
+        Expr::Neg(inner) => eval_expr(inner, data)
+            .into_iter()
+            .map(|v| v.map(|x| -x))
+            .collect(),
+        Expr::Call(name, arg, period) => {
+            let values = eval_expr(arg, data);
+            if name == "sma" {
+                sma_over_options(&values, *period)
+            } else {
+                ema_over_options(&values, *period)
+            }
+        }
+        Expr::Binary(op, lhs, rhs) => eval_expr(lhs, data)
+            .into_iter()
+            .zip(eval_expr(rhs, data))
+            .map(|(a, b)| match (a, b, op) {
+                (Some(a), Some(b), '+') => Some(a + b),
+                (Some(a), Some(b), '-') => Some(a - b),
+                (Some(a), Some(b), '*') => Some(a * b),
+                (Some(a), Some(b), '/') if b != 0.0 => Some(a / b),
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+/// Evaluate a small indicator expression such as
+/// `ema(close, 50) - sma(close, 200)` over `data`.
+///
+/// Emits a point for every bar where the expression is defined. Unknown
+/// functions or series, malformed syntax and expressions past
+/// `MAX_EXPR_NODES` return an error.
+pub fn eval_expression_impl(
+    data: &[PricePoint],
+    expr: &str,
+) -> Result<Vec<IndicatorPoint>, String> {
+    let mut parser = ExprParser {
+        tokens: tokenize_expr(expr)?,
+        pos: 0,
+        nodes: 0,
+    };
+    let ast = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected token {:?}", token));
+    }
+
+    Ok(data
+        .iter()
+        .zip(eval_expr(&ast, data))
+        .filter_map(|(p, v)| v.map(|value| IndicatorPoint { ts: p.ts, value }))
+        .collect())
+}
+
+/// Rescale any indicator output for overlay comparison. A zero-range series
+/// maps to all 0.5 under `MinMax` and all 0.0 under `ZScore`.
+pub fn normalize_series_impl(points: &[IndicatorPoint], mode: NormMode) -> Vec<IndicatorPoint> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    let scale: Box<dyn Fn(f64) -> f64> = match mode {
+        NormMode::MinMax => {
+            let min = points.iter().map(|p| p.value).fold(f64::INFINITY, f64::min);
+            let max = points
+                .iter()
+                .map(|p| p.value)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let range = max - min;
+            if range == 0.0 {
+                Box::new(|_| 0.5)
+            } else {
+                Box::new(move |v| (v - min) / range)
+            }
+        }
+        NormMode::ZScore => {
+            let n = points.len() as f64;
+            let mean = points.iter().map(|p| p.value).sum::<f64>() / n;
+            let var = points.iter().map(|p| (p.value - mean).powi(2)).sum::<f64>() / n;
+            let std_dev = var.sqrt();
+            if std_dev == 0.0 {
+                Box::new(|_| 0.0)
+            } else {
+                Box::new(move |v| (v - mean) / std_dev)
+            }
+        }
+    };
+
+    points
+        .iter()
+        .map(|p| IndicatorPoint {
+            ts: p.ts,
+            value: scale(p.value),
+        })
+        .collect()
+}
+
+/// Return `data` in ascending timestamp order. Newest-first input (first
+/// `ts` greater than last) is reversed; anything else is returned as-is.
+pub fn ensure_ascending_impl(data: &[PricePoint]) -> Vec<PricePoint> {
+    match (data.first(), data.last()) {
+        (Some(first), Some(last)) if first.ts > last.ts => data.iter().rev().cloned().collect(),
+        _ => data.to_vec(),
+    }
+}
+
+/// Align two ascending series by timestamp (merge join).
+pub fn align_series_impl(
+    a: &[PricePoint],
+    b: &[PricePoint],
+    how: JoinKind,
+) -> Vec<(Option<PricePoint>, Option<PricePoint>)> {
+    let outer = how == JoinKind::Outer;
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        if a[i].ts == b[j].ts {
+            result.push((Some(a[i].clone()), Some(b[j].clone())));
+            i += 1;
+            j += 1;
+        } else if a[i].ts < b[j].ts {
+            if outer {
+                result.push((Some(a[i].clone()), None));
+            }
+            i += 1;
+        } else {
+            if outer {
+                result.push((None, Some(b[j].clone())));
+            }
+            j += 1;
+        }
+    }
+
+    if outer {
+        result.extend(a[i..].iter().map(|p| (Some(p.clone()), None)));
+        result.extend(b[j..].iter().map(|p| (None, Some(p.clone()))));
+    }
+
+    result
+}
+
+/// Closes of `a` and `b` at their shared timestamps.
+pub fn align_closes_impl(a: &[PricePoint], b: &[PricePoint]) -> Vec<AlignedCloses> {
+    align_series_impl(a, b, JoinKind::Inner)
+        .into_iter()
+        .filter_map(|pair| match pair {
+            (Some(pa), Some(pb)) => Some(AlignedCloses {
+                ts: pa.ts,
+                a: pa.close,
+                b: pb.close,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `a.close / b.close` at each shared timestamp. A bar where `b.close` is
+/// zero gets `NaN`, so the ratio stays aligned with `calc_spread_impl`.
+pub fn calc_ratio_impl(a: &[PricePoint], b: &[PricePoint]) -> Vec<IndicatorPoint> {
+    align_closes_impl(a, b)
+        .into_iter()
+        .map(|c| IndicatorPoint {
+            ts: c.ts,
+            value: if c.b == 0.0 { f64::NAN } else { c.a / c.b },
+        })
+        .collect()
+}
+
+/// `a.close - hedge_ratio * b.close` at each shared timestamp.
+pub fn calc_spread_impl(
+    a: &[PricePoint],
+    b: &[PricePoint],
+    hedge_ratio: f64,
+) -> Vec<IndicatorPoint> {
+    align_closes_impl(a, b)
+        .into_iter()
+        .map(|c| IndicatorPoint {
+            ts: c.ts,
+            value: c.a - hedge_ratio * c.b,
+        })
+        .collect()
+}
+
+/// Relative performance of `asset` against `benchmark`: both rebased to 100
+/// at their first shared timestamp, then `asset - benchmark` at each shared
+/// timestamp, so positive values mean outperformance since the start. Empty
+/// when there is no overlap or either first shared close is zero.
+pub fn calc_relative_performance_impl(
+    asset: &[PricePoint],
+    benchmark: &[PricePoint],
+) -> Vec<IndicatorPoint> {
+    let closes = align_closes_impl(asset, benchmark);
+    let Some(first) = closes.first().filter(|c| c.a != 0.0 && c.b != 0.0) else {
+        return Vec::new();
+    };
+    let (base_a, base_b) = (first.a, first.b);
+    closes
+        .iter()
+        .map(|c| IndicatorPoint {
+            ts: c.ts,
+            value: 100.0 * c.a / base_a - 100.0 * c.b / base_b,
+        })
+        .collect()
+}
+
+/// Rolling z-score `(value - mean) / stddev` over the trailing `period`
+/// points (population stddev), timestamped at the window's last point. A
+/// flat window yields 0.0.
+pub fn calc_zscore_impl(points: &[IndicatorPoint], period: usize) -> Vec<IndicatorPoint> {
+    if period == 0 || period > points.len() {
+        return Vec::new();
+    }
+
+    points
+        .windows(period)
+        .map(|window| {
+            let n = period as f64;
+            let mean = window.iter().map(|p| p.value).sum::<f64>() / n;
+            let var = window.iter().map(|p| (p.value - mean).powi(2)).sum::<f64>() / n;
+            let last = &window[period - 1];
+            let value = if var == 0.0 {
+                0.0
+            } else {
+                (last.value - mean) / var.sqrt()
+            };
+            IndicatorPoint { ts: last.ts, value }
+        })
+        .collect()
+}
+
+/// Pick `count` points at evenly spaced indices, always keeping the first
+/// and last. A cheap, deterministic alternative to LTTB for thumbnails.
+pub fn uniform_sample_impl(data: &[DataPoint], count: usize) -> Vec<DataPoint> {
+    if data.len() <= count {
+        return data.to_vec();
+    }
+    if count == 0 {
+        return Vec::new();
+    }
+    if count == 1 {
+        return vec![data[0].clone()];
+    }
+
+    let last = data.len() - 1;
+    (0..count)
+        .map(|k| data[k * last / (count - 1)].clone())
+        .collect()
+}
+
+/// LTTB over integer-timestamp points. Timestamps are only widened to `f64`
+/// for the area maths; the returned points carry the original `i64`s.
+pub fn lttb_downsample_i_impl(data: &[DataPointI], threshold: usize) -> Vec<DataPointI> {
+    let widened: Vec<DataPoint> = data
+        .iter()
+        .map(|p| DataPoint {
+            ts: p.ts as f64,
+            value: p.value,
+        })
+        .collect();
+    lttb_downsample_indices_impl(&widened, threshold)
+        .into_iter()
+        .map(|i| data[i].clone())
+        .collect()
+}
+
+/// SMA over integer-timestamp bars; see `calc_sma_impl`.
+pub fn calc_sma_i_impl(data: &[PricePointI], period: usize) -> Vec<IndicatorPointI> {
+    let closes: Vec<f64> = data.iter().map(|p| p.close).collect();
+    sma_over_values(&closes, period)
+        .into_iter()
+        .zip(data.iter().skip(period.saturating_sub(1)))
+        .map(|(value, p)| IndicatorPointI { ts: p.ts, value })
+        .collect()
+}
+
+/// Round `value` to `decimals` places (half away from zero). Pure float
+/// arithmetic, so native and wasm builds agree bit-for-bit.
+pub fn round_value(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    let scaled = value * factor;
+    if !scaled.is_finite() {
+        return value;
+    }
+    scaled.round() / factor
+}
+
+/// Round every point's value in place; see `round_value`.
+pub fn round_to(points: &mut [IndicatorPoint], decimals: u32) {
+    for p in points {
+        p.value = round_value(p.value, decimals);
+    }
+}
+
+/// Collapse runs of bars sharing a timestamp into one bar per `keep`.
+/// Input is assumed ascending-or-equal, so duplicates are adjacent.
+pub fn dedupe_timestamps_impl(data: &[PricePoint], keep: DedupePolicy) -> Vec<PricePoint> {
+    data.chunk_by(|a, b| a.ts == b.ts)
+        .map(|run| match keep {
+            DedupePolicy::KeepFirst => run[0].clone(),
+            DedupePolicy::KeepLast => run[run.len() - 1].clone(),
+            DedupePolicy::Merge => PricePoint {
+                ts: run[0].ts,
+                open: run[0].open,
+                high: run.iter().map(|p| p.high).fold(f64::NEG_INFINITY, f64::max),
+                low: run.iter().map(|p| p.low).fold(f64::INFINITY, f64::min),
+                close: run[run.len() - 1].close,
+                volume: run.iter().map(|p| p.volume).sum(),
+            },
+        })
+        .collect()
+}
+
+/// SMAs for several periods in a single pass over the closes, e.g. for an
+/// MA ribbon. `out[k]` is bit-identical to `calc_sma_impl(data, periods[k])`
+/// (empty for a zero or oversized period).
+pub fn calc_sma_multi_impl(data: &[PricePoint], periods: &[usize]) -> Vec<Vec<IndicatorPoint>> {
+    let valid = |period: usize| period > 0 && period <= data.len();
+    let mut sums: Vec<f64> = vec![0.0; periods.len()];
+    let mut result: Vec<Vec<IndicatorPoint>> = periods
+        .iter()
+        .map(|&period| {
+            if valid(period) {
+                Vec::with_capacity(data.len() - period + 1)
+            } else {
+                Vec::new()
+            }
+        })
+        .collect();
+
+    for (i, p) in data.iter().enumerate() {
+        for (k, &period) in periods.iter().enumerate() {
+            if !valid(period) {
+                continue;
+            }
+            if i < period {
+                sums[k] += p.close;
+                if i + 1 < period {
+                    continue;
+                }
+            } else {
+                sums[k] += p.close - data[i - period].close;
+            }
+            result[k].push(IndicatorPoint {
+                ts: p.ts,
+                value: sums[k] / period as f64,
+            });
+        }
+    }
+
+    result
+}
+
+/// `|a - b| / |b|`, or 0 when `b` is zero.
+fn relative_gap(a: f64, b: f64) -> f64 {
+    if b == 0.0 {
+        0.0
+    } else {
+        ((a - b) / b).abs()
+    }
+}
+
+/// Golden/death crosses between a `fast` and `slow` moving average.
+///
+/// A cross is reported on the bar where the sign of `fast - slow` flips
+/// relative to the last non-zero difference, so touching and bouncing off
+/// the slow MA is not an event. `strength` is the MAs' relative gap on the
+/// cross bar.
+pub fn detect_ma_cross_impl(
+    data: &[PricePoint],
+    fast: usize,
+    slow: usize,
+    ma_kind: MaKind,
+) -> Vec<Signal> {
+    let fast_ma = ma_kind.compute(data, fast);
+    let slow_ma = ma_kind.compute(data, slow);
+    if fast_ma.is_empty() || slow_ma.is_empty() {
+        return Vec::new();
+    }
+
+    let mut events = Vec::new();
+    let mut last_sign = 0.0;
+    for i in fast.max(slow) - 1..data.len() {
+        let f = fast_ma[i + 1 - fast].value;
+        let s = slow_ma[i + 1 - slow].value;
+        let sign = if f > s {
+            1.0
+        } else if f < s {
+            -1.0
+        } else {
+            continue;
+        };
+        if last_sign != 0.0 && sign != last_sign {
+            events.push(Signal {
+                ts: data[i].ts,
+                index: i,
+                kind: if sign > 0.0 {
+                    SignalKind::GoldenCross
+                } else {
+                    SignalKind::DeathCross
+                },
+                strength: relative_gap(f, s),
+            });
+        }
+        last_sign = sign;
+    }
+
+    events
+}
+
+/// Price-by-volume histogram over `[min low, max high]` split into
+/// `num_bins` buckets. Each bar's volume is spread across the buckets its
+/// high-low range overlaps, in proportion to the overlap.
+pub fn calc_volume_profile_impl(data: &[PricePoint], num_bins: usize) -> VolumeProfile {
+    if num_bins == 0 || data.is_empty() {
+        return VolumeProfile::default();
+    }
+
+    let min = data.iter().map(|p| p.low).fold(f64::INFINITY, f64::min);
+    let max = data
+        .iter()
+        .map(|p| p.high)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let bin_size = (max - min) / num_bins as f64;
+    let bucket = |price: f64| -> usize {
+        if bin_size == 0.0 {
+            0
+        } else {
+            (((price - min) / bin_size).floor().max(0.0) as usize).min(num_bins - 1)
+        }
+    };
+
+    let mut volumes = vec![0.0; num_bins];
+    for p in data {
+        let range = p.high - p.low;
+        if range <= 0.0 {
+            volumes[bucket(p.high)] += p.volume;
+            continue;
+        }
+        for (b, volume) in volumes
+            .iter_mut()
+            .enumerate()
+            .take(bucket(p.high) + 1)
+            .skip(bucket(p.low))
+        {
+            let lo = min + b as f64 * bin_size;
+            let overlap = p.high.min(lo + bin_size) - p.low.max(lo);
+            *volume += p.volume * overlap.max(0.0) / range;
+        }
+    }
+
+    let prices: Vec<f64> = (0..num_bins)
+        .map(|b| min + (b as f64 + 0.5) * bin_size)
+        .collect();
+    let poc_index = volumes
+        .iter()
+        .enumerate()
+        .fold(0, |best, (i, v)| if *v > volumes[best] { i } else { best });
+
+    VolumeProfile {
+        poc_price: prices[poc_index],
+        prices,
+        volumes,
+        bin_size,
+        poc_index,
+    }
+}
+
+/// Value Area: grow outward from the POC, each step adding whichever
+/// adjacent bucket has more volume, until `fraction` of the total is
+/// covered. Bounds are bucket edges; an empty profile, or one whose
+/// `prices` and `volumes` differ in length, yields all zeros.
+pub fn calc_value_area_impl(profile: &VolumeProfile, fraction: f64) -> ValueArea {
+    let n = profile.volumes.len();
+    if n == 0 || profile.prices.len() != n || profile.poc_index >= n {
+        return ValueArea::default();
+    }
+
+    let target = profile.volumes.iter().sum::<f64>() * fraction.clamp(0.0, 1.0);
+    let (mut lo, mut hi) = (profile.poc_index, profile.poc_index);
+    let mut volume = profile.volumes[lo];
+    while volume < target && (lo > 0 || hi + 1 < n) {
+        let below = if lo > 0 {
+            profile.volumes[lo - 1]
+        } else {
+            f64::NEG_INFINITY
+        };
+        let above = if hi + 1 < n {
+            profile.volumes[hi + 1]
+        } else {
+            f64::NEG_INFINITY
+        };
+        if above >= below {
+            hi += 1;
+            volume += above;
+        } else {
+            lo -= 1;
+            volume += below;
+        }
+    }
+
+    let half = profile.bin_size / 2.0;
+    ValueArea {
+        high: profile.prices[hi] + half,
+        low: profile.prices[lo] - half,
+        volume,
+    }
+}
+
+/// Percent change of the last close versus the close `h` bars earlier, for
+/// each horizon. `NaN` where there is not enough history or the earlier
+/// close is zero.
+pub fn calc_multi_horizon_change_impl(data: &[PricePoint], horizons: &[usize]) -> Vec<f64> {
+    horizons
+        .iter()
+        .map(|&h| match data.len().checked_sub(h + 1) {
+            Some(i) if data[i].close != 0.0 => {
+                let last = data[data.len() - 1].close;
+                100.0 * (last - data[i].close) / data[i].close
+            }
+            _ => f64::NAN,
+        })
+        .collect()
+}
+
+/// Bollinger Bands over closes using the population standard deviation of
+/// each `period` window, aligned to where the SMA is defined.
+pub fn calc_bollinger_impl(data: &[PricePoint], period: usize, num_std: f64) -> BollingerResult {
+    let middle = calc_sma_impl(data, period);
+    if middle.is_empty() {
+        return BollingerResult::default();
+    }
+
+    let mut upper = Vec::with_capacity(middle.len());
+    let mut lower = Vec::with_capacity(middle.len());
+    for (window, m) in data.windows(period).zip(&middle) {
+        let var = window
+            .iter()
+            .map(|p| (p.close - m.value).powi(2))
+            .sum::<f64>()
+            / period as f64;
+        let offset = num_std * var.sqrt();
+        upper.push(IndicatorPoint {
+            ts: m.ts,
+            value: m.value + offset,
+        });
+        lower.push(IndicatorPoint {
+            ts: m.ts,
+            value: m.value - offset,
+        });
+    }
+
+    BollingerResult {
+        upper,
+        middle,
+        lower,
+    }
+}
+
+/// Bollinger %B: `(close - lower) / (upper - lower)`. Exceeds 0..1 outside
+/// the bands. A bar with zero band width gets `NaN` rather than being
+/// dropped, so the series stays index-aligned with the bands.
+pub fn calc_bollinger_pctb_impl(
+    data: &[PricePoint],
+    period: usize,
+    num_std: f64,
+) -> Vec<IndicatorPoint> {
+    let bands = calc_bollinger_impl(data, period, num_std);
+    if bands.middle.is_empty() {
+        return Vec::new();
+    }
+
+    data[period - 1..]
+        .iter()
+        .zip(bands.upper.iter().zip(&bands.lower))
+        .map(|(p, (u, l))| IndicatorPoint {
+            ts: p.ts,
+            value: if u.value == l.value {
+                f64::NAN
+            } else {
+                (p.close - l.value) / (u.value - l.value)
+            },
+        })
+        .collect()
+}
+
+/// Bollinger Bandwidth: `(upper - lower) / middle`. A bar with a zero middle
+/// band gets `NaN` rather than being dropped, so the series stays
+/// index-aligned with the bands.
+pub fn calc_bollinger_bandwidth_impl(
+    data: &[PricePoint],
+    period: usize,
+    num_std: f64,
+) -> Vec<IndicatorPoint> {
+    let bands = calc_bollinger_impl(data, period, num_std);
+    bands
+        .middle
+        .iter()
+        .zip(bands.upper.iter().zip(&bands.lower))
+        .map(|(m, (u, l))| IndicatorPoint {
+            ts: m.ts,
+            value: if m.value == 0.0 {
+                f64::NAN
+            } else {
+                (u.value - l.value) / m.value
+            },
+        })
+        .collect()
+}
+
+/// Average True Range with Wilder smoothing. The first bar's true range is
+/// its high-low; the first ATR (at bar `period - 1`) is the plain mean.
+pub fn calc_atr_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
+    if period == 0 || period > data.len() {
+        return Vec::new();
+    }
+
+    let true_range = |i: usize| -> f64 {
+        let p = &data[i];
+        if i == 0 {
+            return p.high - p.low;
+        }
+        let prev_close = data[i - 1].close;
+        (p.high - p.low)
+            .max((p.high - prev_close).abs())
+            .max((p.low - prev_close).abs())
+    };
+
+    let mut atr: f64 = (0..period).map(true_range).sum::<f64>() / period as f64;
+    let mut result: Vec<IndicatorPoint> = Vec::with_capacity(data.len() - period + 1);
+    result.push(IndicatorPoint {
+        ts: data[period - 1].ts,
+        value: atr,
+    });
+    for (i, p) in data.iter().enumerate().skip(period) {
+        atr = (atr * (period - 1) as f64 + true_range(i)) / period as f64;
+        result.push(IndicatorPoint {
+            ts: p.ts,
+            value: atr,
+        });
+    }
+
+    result
+}
+
+/// Keltner Channels: `EMA(close, ema_period) ± mult * ATR(atr_period)`,
+/// aligned to where both are defined.
+pub fn calc_keltner_impl(
+    data: &[PricePoint],
+    ema_period: usize,
+    atr_period: usize,
+    mult: f64,
+) -> KeltnerResult {
+    let ema = calc_ema_impl(data, ema_period);
+    let atr = calc_atr_impl(data, atr_period);
+    if ema.is_empty() || atr.is_empty() {
+        return KeltnerResult::default();
+    }
+
+    let start = ema_period.max(atr_period) - 1;
+    let mut out = KeltnerResult::default();
+    for i in start..data.len() {
+        let mid = ema[i + 1 - ema_period].value;
+        let offset = mult * atr[i + 1 - atr_period].value;
+        let ts = data[i].ts;
+        out.upper.push(IndicatorPoint {
+            ts,
+            value: mid + offset,
+        });
+        out.middle.push(IndicatorPoint { ts, value: mid });
+        out.lower.push(IndicatorPoint {
+            ts,
+            value: mid - offset,
+        });
+    }
+    out
+}
+
+/// TTM squeeze "on": per bar, whether the Bollinger Bands sit entirely
+/// inside the Keltner Channels. One flag per bar from the first bar where
+/// both are defined; empty if either cannot be computed.
+pub fn detect_squeeze_impl(
+    data: &[PricePoint],
+    bb_period: usize,
+    bb_std: f64,
+    kc_ema: usize,
+    kc_atr: usize,
+    kc_mult: f64,
+) -> Vec<bool> {
+    let bb = calc_bollinger_impl(data, bb_period, bb_std);
+    let kc = calc_keltner_impl(data, kc_ema, kc_atr, kc_mult);
+    if bb.middle.is_empty() || kc.middle.is_empty() {
+        return Vec::new();
+    }
+
+    let kc_start = kc_ema.max(kc_atr) - 1;
+    let start = kc_start.max(bb_period - 1);
+    (start..data.len())
+        .map(|i| {
+            let b = i + 1 - bb_period;
+            let k = i - kc_start;
+            bb.upper[b].value < kc.upper[k].value && bb.lower[b].value > kc.lower[k].value
+        })
+        .collect()
+}
+
+/// Validate a `max_results` coming from JS. Negative, NaN and fractional
+/// inputs would otherwise wrap to huge `usize`s at the boundary; here they
+/// map to 0 (negative / NaN) or are floored, and the result is clamped to
+/// `len`.
+pub fn clamp_max_results(max_results: f64, len: usize) -> usize {
+    if max_results.is_nan() || max_results <= 0.0 {
+        return 0;
+    }
+    if max_results >= len as f64 {
+        len
+    } else {
+        max_results as usize
+    }
+}
+
+/// Each bar mapped to `source` (close, typical, median, ...), for plotting a
+/// derived price line with the same formulas the indicators use.
+pub fn calc_price_series_impl(data: &[PricePoint], source: PriceSource) -> Vec<IndicatorPoint> {
+    data.iter()
+        .map(|p| IndicatorPoint {
+            ts: p.ts,
+            value: source.value(p),
+        })
+        .collect()
+}
+
+/// Non-positive values are clamped to this before taking a logarithm.
+const LOG_EPSILON: f64 = 1e-12;
+
+/// `base` if it is a usable logarithm base, otherwise `e`.
+fn log_base_or_e(base: f64) -> f64 {
+    if base > 0.0 && base != 1.0 && base.is_finite() {
+        base
+    } else {
+        std::f64::consts::E
+    }
+}
+
+/// Map each value to `log_base(value)` so overlays line up with a log price
+/// axis. Non-positive values are clamped to `LOG_EPSILON`; an invalid base
+/// (non-positive, 1 or non-finite) falls back to the natural log.
+pub fn to_log_space_impl(points: &[IndicatorPoint], base: f64) -> Vec<IndicatorPoint> {
+    let base = log_base_or_e(base);
+    points
+        .iter()
+        .map(|p| IndicatorPoint {
+            ts: p.ts,
+            value: p.value.max(LOG_EPSILON).log(base),
+        })
+        .collect()
+}
+
+/// Inverse of `to_log_space_impl`: `base^value`.
+pub fn from_log_space_impl(points: &[IndicatorPoint], base: f64) -> Vec<IndicatorPoint> {
+    let base = log_base_or_e(base);
+    points
+        .iter()
+        .map(|p| IndicatorPoint {
+            ts: p.ts,
+            value: base.powf(p.value),
+        })
+        .collect()
+}
+
+/// Shares to buy so that a move from `entry` to `stop` loses `risk_pct`
+/// (a fraction, 0.01 = 1%) of `account_equity`. Returns 0 for a zero or
+/// invalid stop distance.
+pub fn position_size_impl(account_equity: f64, risk_pct: f64, entry: f64, stop: f64) -> f64 {
+    shares_for_risk(account_equity * risk_pct, (entry - stop).abs())
+}
+
+/// `position_size_impl` with the stop `atr * atr_mult` away from entry.
+pub fn position_size_atr_impl(account_equity: f64, risk_pct: f64, atr: f64, atr_mult: f64) -> f64 {
+    shares_for_risk(account_equity * risk_pct, atr * atr_mult)
+}
+
+fn shares_for_risk(risk_amount: f64, stop_distance: f64) -> f64 {
+    if stop_distance.is_finite() && stop_distance > 0.0 {
+        risk_amount / stop_distance
+    } else {
+        0.0
+    }
+}
+
+/// Long-only MA crossover backtest: enter on a golden cross and exit on a
+/// death cross, each filled at the open of the bar after the signal. A
+/// position still open at the end is closed at the last close for the
+/// stats. Zeroed result when the MAs cannot be computed.
+pub fn backtest_cross_impl(
+    data: &[PricePoint],
+    fast: usize,
+    slow: usize,
+    ma_kind: MaKind,
+) -> BacktestResult {
+    if fast == 0 || slow == 0 || fast.max(slow) > data.len() {
+        return BacktestResult::default();
+    }
+
+    let events = detect_ma_cross_impl(data, fast, slow, ma_kind);
+    let mut fills = events
+        .iter()
+        .filter(|e| e.index + 1 < data.len())
+        .peekable();
+
+    let mut cash = 1.0;
+    let mut entry: Option<f64> = None;
+    let mut trades = 0;
+    let mut wins = 0;
+    let mut equity = Vec::with_capacity(data.len());
+
+    for (i, p) in data.iter().enumerate() {
+        if let Some(event) = fills.next_if(|e| e.index + 1 == i) {
+            match (event.kind.is_bullish(), entry) {
+                (true, None) => entry = Some(p.open),
+                (false, Some(price)) => {
+                    cash *= p.open / price;
+                    trades += 1;
+                    wins += usize::from(p.open > price);
+                    entry = None;
+                }
+                _ => {}
+            }
+        }
+
+        let value = match entry {
+            Some(price) => cash * p.close / price,
+            None => cash,
+        };
+        equity.push(IndicatorPoint { ts: p.ts, value });
+    }
+
+    if let (Some(price), Some(last)) = (entry, data.last()) {
+        trades += 1;
+        wins += usize::from(last.close > price);
+    }
+
+    backtest_result(equity, trades, wins, trades)
+}
+
+/// Assemble a `BacktestResult`; `win_rate` is `wins / closed`.
+fn backtest_result(
+    equity: Vec<IndicatorPoint>,
+    trades: usize,
+    wins: usize,
+    closed: usize,
+) -> BacktestResult {
+    let values: Vec<f64> = equity.iter().map(|e| e.value).collect();
+    BacktestResult {
+        total_return: values.last().map_or(0.0, |v| v - 1.0),
+        max_drawdown: max_drawdown_over_values(&values),
+        equity,
+        trades,
+        win_rate: if closed == 0 {
+            0.0
+        } else {
+            wins as f64 / closed as f64
+        },
+    }
+}
+
+/// Largest peak-to-trough decline over `values`, as a positive fraction of
+/// the peak (0.25 = a 25% drawdown). Non-positive peaks are skipped.
+pub fn max_drawdown_over_values(values: &[f64]) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut max_dd: f64 = 0.0;
+    for &v in values {
+        peak = peak.max(v);
+        if peak > 0.0 {
+            max_dd = max_dd.max(1.0 - v / peak);
+        }
+    }
+    max_dd
+}
+
+/// Maximum drawdown of the closes; see `max_drawdown_over_values`.
+pub fn calc_drawdown_impl(data: &[PricePoint]) -> f64 {
+    let closes: Vec<f64> = data.iter().map(|p| p.close).collect();
+    max_drawdown_over_values(&closes)
+}
+
+/// Underwater series: `close / running_peak - 1` at every bar, so 0 at each
+/// new high and negative below it. The first bar is always 0; bars while the
+/// running peak is non-positive read 0, as in `max_drawdown_over_values`.
+pub fn calc_rolling_drawdown_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
+    let mut peak = f64::NEG_INFINITY;
+    data.iter()
+        .map(|p| {
+            peak = peak.max(p.close);
+            let value = if peak > 0.0 {
+                (p.close / peak - 1.0).min(0.0)
+            } else {
+                0.0
+            };
+            IndicatorPoint { ts: p.ts, value }
+        })
+        .collect()
+}
+
+/// Backtest an explicit position series: `signals[i]` (+1 long, -1 short,
+/// 0 flat; missing entries are flat) is entered at bar `i`'s close and
+/// earns the close-to-close return to bar `i + 1`.
+///
+/// `trades` counts position changes; `win_rate` is the share of non-flat
+/// holding periods (each ending at the next change or the last bar) that
+/// grew equity.
+pub fn backtest_signals_impl(data: &[PricePoint], signals: &[i32]) -> BacktestResult {
+    let Some(first) = data.first() else {
+        return BacktestResult::default();
+    };
+    let position = |i: usize| signals.get(i).map_or(0, |s| s.signum());
+
+    let mut equity = vec![IndicatorPoint {
+        ts: first.ts,
+        value: 1.0,
+    }];
+    let mut value = 1.0;
+    let mut trades = 0;
+    let (mut held, mut wins) = (0, 0);
+    let mut held_from = 1.0;
+    let mut prev_pos = 0;
+
+    for i in 0..data.len() {
+        if i > 0 {
+            let prev_close = data[i - 1].close;
+            if prev_close != 0.0 {
+                value *= 1.0 + prev_pos as f64 * (data[i].close / prev_close - 1.0);
+            }
+            equity.push(IndicatorPoint {
+                ts: data[i].ts,
+                value,
+            });
+        }
+
+        let pos = position(i);
+        if pos != prev_pos {
+            trades += 1;
+            if prev_pos != 0 {
+                held += 1;
+                wins += usize::from(value > held_from);
+            }
+            held_from = value;
+            prev_pos = pos;
+        }
+    }
+    if prev_pos != 0 {
+        held += 1;
+        wins += usize::from(value > held_from);
+    }
+
+    backtest_result(equity, trades, wins, held)
+}
+
+/// Simple close-to-close returns `close[i] / close[i - 1] - 1`, timestamped
+/// at bar `i`. Bars following a zero close are skipped.
+pub fn calc_returns_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
+    data.windows(2)
+        .filter(|w| w[0].close != 0.0)
+        .map(|w| IndicatorPoint {
+            ts: w[1].ts,
+            value: w[1].close / w[0].close - 1.0,
+        })
+        .collect()
+}
+
+/// Growth since the first bar, `close[i] / close[0] - 1`, for a
+/// "performance since" line. Empty when the first close is zero.
+pub fn calc_cumulative_return_impl(data: &[PricePoint]) -> Vec<IndicatorPoint> {
+    let Some(base) = data.first().map(|p| p.close).filter(|&c| c != 0.0) else {
+        return Vec::new();
+    };
+    data.iter()
+        .map(|p| IndicatorPoint {
+            ts: p.ts,
+            value: p.close / base - 1.0,
+        })
+        .collect()
+}
+
+/// Trailing `period`-bar return `close[i] / close[i - period] - 1`,
+/// timestamped at bar `i`. Bars whose base close is zero are skipped.
+pub fn calc_rolling_return_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
+    if period == 0 || period >= data.len() {
+        return Vec::new();
+    }
+    data.windows(period + 1)
+        .filter(|w| w[0].close != 0.0)
+        .map(|w| IndicatorPoint {
+            ts: w[period].ts,
+            value: w[period].close / w[0].close - 1.0,
+        })
+        .collect()
+}
+
+/// Rebase closes so the series starts at `base`: `base * close / first_close`
+/// (100 for the usual comparison chart). Empty when the first close is zero.
+pub fn rebase_series_impl(data: &[PricePoint], base: f64) -> Vec<IndicatorPoint> {
+    let Some(first) = data.first().map(|p| p.close).filter(|&c| c != 0.0) else {
+        return Vec::new();
+    };
+    data.iter()
+        .map(|p| IndicatorPoint {
+            ts: p.ts,
+            value: base * p.close / first,
+        })
+        .collect()
+}
+
+/// `rebase_series_impl` applied to each series against its own first
+/// close, so every series starts at `base`.
+pub fn rebase_multi_impl(series: &[Vec<PricePoint>], base: f64) -> Vec<Vec<IndicatorPoint>> {
+    series.iter().map(|s| rebase_series_impl(s, base)).collect()
+}
+
+/// Deviations at or below this are rounding noise from a constant series.
+const MIN_RETURN_DEVIATION: f64 = 1e-12;
+
+/// Annualized Sharpe ratio of per-period `returns` over a per-period
+/// `risk_free` rate, using the sample standard deviation of the excess
+/// returns. 0 for fewer than two returns or zero deviation.
+pub fn calc_sharpe_impl(returns: &[f64], risk_free: f64, periods_per_year: f64) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().map(|r| r - risk_free).sum::<f64>() / n;
+    let var = returns
+        .iter()
+        .map(|r| (r - risk_free - mean).powi(2))
+        .sum::<f64>()
+        / (n - 1.0);
+    if var.sqrt() <= MIN_RETURN_DEVIATION {
+        return 0.0;
+    }
+    mean / var.sqrt() * periods_per_year.sqrt()
+}
+
+/// Annualized Sortino ratio: like `calc_sharpe_impl` but divided by the
+/// downside deviation `sqrt(sum(min(excess, 0)^2) / n)`. 0 when there are
+/// no returns or no negative excess returns.
+pub fn calc_sortino_impl(returns: &[f64], risk_free: f64, periods_per_year: f64) -> f64 {
+    if returns.is_empty() {
+        return 0.0;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().map(|r| r - risk_free).sum::<f64>() / n;
+    let downside = returns
+        .iter()
+        .map(|r| (r - risk_free).min(0.0).powi(2))
+        .sum::<f64>()
+        / n;
+    if downside.sqrt() <= MIN_RETURN_DEVIATION {
+        return 0.0;
+    }
+    mean / downside.sqrt() * periods_per_year.sqrt()
+}
+
+/// Annualized volatility: sample standard deviation of per-period
+/// `returns` times `sqrt(periods_per_year)`; 0 for fewer than two returns.
+pub fn annualized_volatility(returns: &[f64], periods_per_year: f64) -> f64 {
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let var = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (var * periods_per_year).sqrt()
+}
+
+/// Total return, CAGR over the `len - 1` bar intervals, annualized
+/// volatility and max drawdown of the closes. Zeroed for fewer than two
+/// bars or a non-positive first close.
+pub fn summary_stats_impl(data: &[PricePoint], periods_per_year: f64) -> SummaryStats {
+    let (Some(first), Some(last)) = (data.first(), data.last()) else {
+        return SummaryStats::default();
+    };
+    if data.len() < 2 || first.close <= 0.0 {
+        return SummaryStats::default();
+    }
+
+    let growth = last.close / first.close;
+    let periods = (data.len() - 1) as f64;
+    let returns: Vec<f64> = calc_returns_impl(data).iter().map(|r| r.value).collect();
+    SummaryStats {
+        total_return: growth - 1.0,
+        cagr: growth.powf(periods_per_year / periods) - 1.0,
+        volatility: annualized_volatility(&returns, periods_per_year),
+        max_drawdown: calc_drawdown_impl(data),
+    }
+}
+
+/// Wilder's Parabolic SAR as `(sar, uptrend)` per bar from bar 1 onward.
+///
+/// The initial trend is up when the second close is not below the first,
+/// with the SAR starting at the first bar's low (high for a downtrend).
+fn psar_series(data: &[PricePoint], step: f64, max_step: f64) -> Vec<(f64, bool)> {
+    if data.len() < 2 {
+        return Vec::new();
+    }
+
+    let mut up = data[1].close >= data[0].close;
+    let mut sar = if up { data[0].low } else { data[0].high };
+    let mut ep = if up {
+        data[0].high.max(data[1].high)
+    } else {
+        data[0].low.min(data[1].low)
+    };
+    let mut af = step;
+
+    let mut out = Vec::with_capacity(data.len() - 1);
+    out.push((sar, up));
+    for i in 2..data.len() {
+        let p = &data[i];
+        sar += af * (ep - sar);
+        if up {
+            sar = sar.min(data[i - 1].low).min(data[i - 2].low);
+            if p.low < sar {
+                up = false;
+                sar = ep;
+                ep = p.low;
+                af = step;
+            } else if p.high > ep {
+                ep = p.high;
+                af = (af + step).min(max_step);
+            }
+        } else {
+            sar = sar.max(data[i - 1].high).max(data[i - 2].high);
+            if p.high > sar {
+                up = true;
+                sar = ep;
+                ep = p.high;
+                af = step;
+            } else if p.low < ep {
+                ep = p.low;
+                af = (af + step).min(max_step);
+            }
+        }
+        out.push((sar, up));
+    }
+    out
+}
+
+/// Parabolic SAR (typically `step` 0.02, `max_step` 0.2), one point per bar
+/// from the second bar onward.
+pub fn calc_psar_impl(data: &[PricePoint], step: f64, max_step: f64) -> Vec<IndicatorPoint> {
+    psar_series(data, step, max_step)
+        .into_iter()
+        .zip(&data[1.min(data.len())..])
+        .map(|((value, _), p)| IndicatorPoint { ts: p.ts, value })
+        .collect()
+}
+
+/// PSAR trend flips as signals on the flip bar: `PsarBuy` when the SAR
+/// moves below price, `PsarSell` when it moves above. `strength` is the
+/// SAR's relative distance from the close.
+pub fn psar_signals_impl(data: &[PricePoint], step: f64, max_step: f64) -> Vec<Signal> {
+    let series = psar_series(data, step, max_step);
+    series
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| w[0].1 != w[1].1)
+        .map(|(k, w)| {
+            let index = k + 2;
+            let (sar, up) = w[1];
+            Signal {
+                ts: data[index].ts,
+                index,
+                kind: if up {
+                    SignalKind::PsarBuy
+                } else {
+                    SignalKind::PsarSell
+                },
+                strength: relative_gap(sar, data[index].close),
+            }
+        })
+        .collect()
+}
+
+/// Interleave several signal streams in timestamp order. The sort is
+/// stable, so same-timestamp signals keep their stream order.
+pub fn merge_signals_impl(signals: &[Vec<Signal>]) -> Vec<Signal> {
+    let mut merged: Vec<Signal> = signals.iter().flatten().cloned().collect();
+    merged.sort_by(|a, b| a.ts.total_cmp(&b.ts));
+    merged
+}
+
+/// ATR trailing stop. Starts long at bar `atr_period - 1` with the stop at
+/// `close - multiplier * ATR`; in an uptrend the stop only rises, and a
+/// close below it flips to a downtrend stop at `close + multiplier * ATR`,
+/// which then only falls until a close above it flips back.
+pub fn calc_atr_stop_impl(
+    data: &[PricePoint],
+    atr_period: usize,
+    multiplier: f64,
+) -> Vec<IndicatorPoint> {
+    let atr = calc_atr_impl(data, atr_period);
+    if atr.is_empty() {
+        return Vec::new();
+    }
+    let mut out: Vec<IndicatorPoint> = Vec::with_capacity(atr.len());
+    let mut up = true;
+    let mut stop = f64::NAN;
+
+    for (p, a) in data[atr_period.saturating_sub(1)..].iter().zip(&atr) {
+        let offset = multiplier * a.value;
+        if stop.is_nan() {
+            stop = p.close - offset;
+        } else if up {
+            if p.close < stop {
+                up = false;
+                stop = p.close + offset;
+            } else {
+                stop = stop.max(p.close - offset);
+            }
+        } else if p.close > stop {
+            up = true;
+            stop = p.close - offset;
+        } else {
+            stop = stop.min(p.close + offset);
+        }
+        out.push(IndicatorPoint {
+            ts: p.ts,
+            value: stop,
+        });
+    }
+
+    out
+}
+
+/// Leading bars without a value for `indicator` at `period`, so the UI can
+/// shade the warm-up region. Names are the crate's indicator names (`atr`,
+/// `bollinger`, `psar`, ...), matched case-insensitively: a superset of the
+/// `IndicatorSpec::kind` values `compute_indicator_impl` accepts. `None` for
+/// an unknown indicator.
+pub fn warmup_bars_impl(indicator: &str, period: usize) -> Option<usize> {
+    match indicator.to_ascii_lowercase().as_str() {
+        "sma" | "ema" | "atr" | "bollinger" | "keltner" | "envelope" | "rolling_vwap" | "rvol" => {
+            Some(period.saturating_sub(1))
+        }
+        "rsi" | "rolling_return" => Some(period),
+        "vwap" | "price" | "cumulative_return" => Some(0),
+        "psar" | "returns" => Some(1),
+        _ => None,
+    }
+}
+
+/// Relative volume: each bar's volume over the mean volume of the trailing
+/// `avg_period`-bar window ending at it, so values above 1 mark unusually
+/// active bars. A window with no volume yields 0.0.
+pub fn calc_rvol_impl(data: &[PricePoint], avg_period: usize) -> Vec<IndicatorPoint> {
+    if avg_period == 0 || avg_period > data.len() {
+        return Vec::new();
+    }
+
+    let mut window_sum: f64 = data[..avg_period].iter().map(|p| p.volume).sum();
+    let mut result = Vec::with_capacity(data.len() - avg_period + 1);
+    for i in avg_period - 1..data.len() {
+        if i >= avg_period {
+            window_sum += data[i].volume - data[i - avg_period].volume;
+        }
+        let avg = window_sum / avg_period as f64;
+        let value = if avg == 0.0 {
+            0.0
+        } else {
+            data[i].volume / avg
+        };
+        result.push(IndicatorPoint {
+            ts: data[i].ts,
+            value,
+        });
+    }
+
+    result
+}
+
+/// Percentage envelope around an SMA or EMA of `period` bars: bands at
+/// `middle * (1 ± pct)`, with `pct` a fraction (0.05 for 5%). Aligned to the
+/// moving average; empty when it is undefined.
+pub fn calc_envelope_impl(
+    data: &[PricePoint],
+    period: usize,
+    pct: f64,
+    ma_kind: MaKind,
+) -> EnvelopeResult {
+    let middle = ma_kind.compute(data, period);
+    let band = |factor: f64| {
+        middle
+            .iter()
+            .map(|p| IndicatorPoint {
+                ts: p.ts,
+                value: p.value * factor,
+            })
+            .collect()
+    };
+    EnvelopeResult {
+        upper: band(1.0 + pct),
+        lower: band(1.0 - pct),
+        middle,
+    }
+}
+
+/// Bars whose open differs from the previous close by at least `min_pct`
+/// (a fraction, 0.02 for 2%), e.g. overnight gaps. Bars after a zero close
+/// are skipped.
+pub fn detect_price_gaps_impl(data: &[PricePoint], min_pct: f64) -> Vec<GapEvent> {
+    data.windows(2)
+        .filter(|w| w[0].close != 0.0)
+        .filter_map(|w| {
+            let pct = w[1].open / w[0].close - 1.0;
+            if pct.abs() < min_pct || pct == 0.0 {
+                return None;
+            }
+            let kind = if pct > 0.0 {
+                GapKind::GapUp
+            } else {
+                GapKind::GapDown
+            };
+            Some(GapEvent {
+                ts: w[1].ts,
+                kind,
+                pct,
+            })
+        })
+        .collect()
+}
+
+/// Simple returns of two series inner-joined on timestamp, as
+/// `(ts, asset_return, benchmark_return)`. Steps from a zero close in either
+/// series are skipped.
+fn aligned_returns(asset: &[PricePoint], benchmark: &[PricePoint]) -> Vec<(f64, f64, f64)> {
+    let joined = align_series_impl(asset, benchmark, JoinKind::Inner);
+    joined
+        .windows(2)
+        .filter_map(|w| match (&w[0], &w[1]) {
+            ((Some(a0), Some(b0)), (Some(a1), Some(b1))) if a0.close != 0.0 && b0.close != 0.0 => {
+                Some((a1.ts, a1.close / a0.close - 1.0, b1.close / b0.close - 1.0))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// `cov(asset, benchmark) / var(benchmark)` over paired returns; 0 when the
+/// benchmark does not move.
+fn beta_of(returns: &[(f64, f64, f64)]) -> f64 {
+    let n = returns.len() as f64;
+    let mean_a = returns.iter().map(|r| r.1).sum::<f64>() / n;
+    let mean_b = returns.iter().map(|r| r.2).sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for &(_, a, b) in returns {
+        cov += (a - mean_a) * (b - mean_b);
+        var += (b - mean_b) * (b - mean_b);
+    }
+    if var <= MIN_RETURN_DEVIATION * MIN_RETURN_DEVIATION {
+        0.0
+    } else {
+        cov / var
+    }
+}
+
+/// Beta of `asset` against `benchmark` over all timestamps they share.
+/// 0 for fewer than two shared returns.
+pub fn calc_beta_impl(asset: &[PricePoint], benchmark: &[PricePoint]) -> f64 {
+    let returns = aligned_returns(asset, benchmark);
+    if returns.len() < 2 {
+        return 0.0;
+    }
+    beta_of(&returns)
+}
+
+/// Beta over each trailing `period` of shared returns, timestamped at the
+/// window's last shared bar. Empty when the overlap is too short.
+pub fn calc_rolling_beta_impl(
+    asset: &[PricePoint],
+    benchmark: &[PricePoint],
+    period: usize,
+) -> Vec<IndicatorPoint> {
+    let returns = aligned_returns(asset, benchmark);
+    if period < 2 || period > returns.len() {
+        return Vec::new();
+    }
+    returns
+        .windows(period)
+        .map(|w| IndicatorPoint {
+            ts: w[period - 1].0,
+            value: beta_of(w),
+        })
+        .collect()
+}
+
+/// Candle direction per bar: +1 when `close >= open`, -1 otherwise.
+pub fn candle_directions_impl(data: &[PricePoint]) -> Vec<i32> {
+    data.iter()
+        .map(|p| if p.close >= p.open { 1 } else { -1 })
+        .collect()
+}
+
+/// Split indicator points into parallel `ts` / `value` columns.
+pub fn to_indicator_columns_impl(points: &[IndicatorPoint]) -> IndicatorColumns {
+    IndicatorColumns {
+        ts: points.iter().map(|p| p.ts).collect(),
+        value: points.iter().map(|p| p.value).collect(),
+    }
+}
+
+/// Weight given to each new reading by the Schaff Trend Cycle's smoothing.
+const STC_SMOOTHING: f64 = 0.5;
+
+/// Stochastic of `values` over `cycle`-value windows, smoothed by
+/// `STC_SMOOTHING`. `out[j]` corresponds to `values[cycle - 1 + j]`; a window
+/// with no range reads 50.
+fn smoothed_stochastic(values: &[f64], cycle: usize) -> Vec<f64> {
+    let mut prev: Option<f64> = None;
+    values
+        .windows(cycle)
+        .map(|w| {
+            let lo = w.iter().copied().fold(f64::INFINITY, f64::min);
+            let hi = w.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let raw = if hi > lo {
+                (w[cycle - 1] - lo) / (hi - lo) * 100.0
+            } else {
+                50.0
+            };
+            let next = prev.map_or(raw, |p| p + STC_SMOOTHING * (raw - p));
+            prev = Some(next);
+            next
+        })
+        .collect()
+}
+
+/// Schaff Trend Cycle: the MACD line (EMA `fast` minus EMA `slow`) passed
+/// through a smoothed `cycle`-bar stochastic twice, giving a 0..=100
+/// oscillator that turns faster than MACD.
+///
+/// The MACD line starts at bar `max(fast, slow) - 1` and each stochastic
+/// pass needs another `cycle - 1` bars, so the first value is at bar
+/// `max(fast, slow) + 2 * cycle - 3`. Empty for a zero parameter or too
+/// little data.
+pub fn calc_stc_impl(
+    data: &[PricePoint],
+    fast: usize,
+    slow: usize,
+    cycle: usize,
+) -> Vec<IndicatorPoint> {
+    let long = fast.max(slow);
+    if fast == 0 || slow == 0 || cycle == 0 || long > data.len() {
+        return Vec::new();
+    }
+
+    let closes: Vec<f64> = data.iter().map(|p| p.close).collect();
+    let fast_ema = ema_over_values(&closes, fast);
+    let slow_ema = ema_over_values(&closes, slow);
+    let macd: Vec<f64> = (long - 1..data.len())
+        .map(|i| fast_ema[i + 1 - fast] - slow_ema[i + 1 - slow])
+        .collect();
+
+    let first = smoothed_stochastic(&macd, cycle);
+    let second = smoothed_stochastic(&first, cycle);
+    let start = long + 2 * (cycle - 1) - 1;
+    second
+        .into_iter()
+        .enumerate()
+        .map(|(j, value)| IndicatorPoint {
+            ts: data[start + j].ts,
+            value,
+        })
+        .collect()
+}
+
+/// Signed run length of consecutive up (+) or down (-) closes ending at each
+/// bar; 0 on an unchanged close and on the first bar.
+fn close_streaks(data: &[PricePoint]) -> Vec<f64> {
+    let mut streak: f64 = 0.0;
+    let mut out = Vec::with_capacity(data.len());
+    out.extend(data.first().map(|_| 0.0));
+    for w in data.windows(2) {
+        streak = if w[1].close > w[0].close {
+            streak.max(0.0) + 1.0
+        } else if w[1].close < w[0].close {
+            streak.min(0.0) - 1.0
+        } else {
+            0.0
+        };
+        out.push(streak);
+    }
+    out
+}
+
+/// Connors RSI: the mean of the `rsi_period` RSI of close, the
+/// `streak_rsi_period` RSI of the up/down streak length, and the percent rank
+/// of the one-bar return among the previous `pct_rank_period` returns.
+///
+/// Starts at the first bar where all three are defined,
+/// `max(rsi_period, streak_rsi_period, pct_rank_period + 1)`. Returns after a
+/// zero close count as 0.
+pub fn calc_connors_rsi_impl(
+    data: &[PricePoint],
+    rsi_period: usize,
+    streak_rsi_period: usize,
+    pct_rank_period: usize,
+) -> Vec<IndicatorPoint> {
+    let rank_start = pct_rank_period.saturating_add(1);
+    let start = rsi_period.max(streak_rsi_period).max(rank_start);
+    if rsi_period == 0 || streak_rsi_period == 0 || pct_rank_period == 0 || start >= data.len() {
+        return Vec::new();
+    }
+
+    let with_closes = |closes: Vec<f64>, from: usize| -> Vec<PricePoint> {
+        data[from..]
+            .iter()
+            .zip(closes)
+            .map(|(p, close)| PricePoint { close, ..p.clone() })
+            .collect()
+    };
+    let streaks = with_closes(close_streaks(data), 0);
+    let returns: Vec<f64> = data
+        .windows(2)
+        .map(|w| {
+            if w[0].close == 0.0 {
+                0.0
+            } else {
+                w[1].close / w[0].close - 1.0
+            }
+        })
+        .collect();
+    let returns = with_closes(returns, 1);
+
+    let rsi = calc_rsi_impl(data, rsi_period);
+    let streak_rsi = calc_rsi_impl(&streaks, streak_rsi_period);
+    let rank = calc_percent_rank_impl(&returns, pct_rank_period, PriceSource::Close);
+
+    (start..data.len())
+        .map(|i| IndicatorPoint {
+            ts: data[i].ts,
+            value: (rsi[i - rsi_period].value
+                + streak_rsi[i - streak_rsi_period].value
+                + rank[i - rank_start].value)
+                / 3.0,
+        })
+        .collect()
+}
+
+/// True Strength Index: one-bar momentum smoothed by an EMA of `long` then
+/// one of `short`, over the absolute momentum smoothed the same way, times
+/// 100. The signal line is a `signal`-period EMA of the TSI.
+///
+/// Momentum starts at bar 1, so the TSI starts at bar `long + short - 1`
+/// and the signal `signal - 1` bars later. A zero denominator (no movement)
+/// reads 0. Empty when the TSI is undefined.
+pub fn calc_tsi_impl(data: &[PricePoint], long: usize, short: usize, signal: usize) -> TsiResult {
+    if long == 0 || short == 0 || signal == 0 || data.len() < long + short {
+        return TsiResult::default();
+    }
+
+    let momentum: Vec<f64> = data.windows(2).map(|w| w[1].close - w[0].close).collect();
+    let abs_momentum: Vec<f64> = momentum.iter().map(|m| m.abs()).collect();
+    let smooth = ema_over_values(&ema_over_values(&momentum, long), short);
+    let smooth_abs = ema_over_values(&ema_over_values(&abs_momentum, long), short);
+
+    let start = long + short - 1;
+    let tsi: Vec<IndicatorPoint> = smooth
+        .iter()
+        .zip(&smooth_abs)
+        .enumerate()
+        .map(|(j, (m, a))| IndicatorPoint {
+            ts: data[start + j].ts,
+            value: if *a == 0.0 { 0.0 } else { 100.0 * m / a },
+        })
+        .collect();
+
+    let values: Vec<f64> = tsi.iter().map(|p| p.value).collect();
+    let signal = ema_over_values(&values, signal)
+        .into_iter()
+        .enumerate()
+        .map(|(j, value)| IndicatorPoint {
+            ts: tsi[signal - 1 + j].ts,
+            value,
+        })
+        .collect();
+
+    TsiResult { tsi, signal }
+}
+
+/// Summarize `points` for triage: count, first/last timestamps, the
+/// min/max/mean of the finite values, and how many values are NaN or
+/// infinite. All-zero stats for an empty series; min/max/mean stay 0 when no
+/// value is finite.
+pub fn describe_series_impl(points: &[IndicatorPoint]) -> SeriesStats {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return SeriesStats::default();
+    };
+
+    let finite: Vec<f64> = points
+        .iter()
+        .map(|p| p.value)
+        .filter(|v| v.is_finite())
+        .collect();
+    let mut stats = SeriesStats {
+        count: points.len(),
+        first_ts: first.ts,
+        last_ts: last.ts,
+        nan_count: points.len() - finite.len(),
+        ..SeriesStats::default()
+    };
+    if !finite.is_empty() {
+        stats.min = finite.iter().copied().fold(f64::INFINITY, f64::min);
+        stats.max = finite.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        stats.mean = finite.iter().sum::<f64>() / finite.len() as f64;
+    }
+    stats
+}
+
+/// Data-quality problems in `data`, one entry per issue per bar, in bar
+/// order. A bar with a non-finite field reports only `NonFinite`; otherwise
+/// `high < low`, and an open or close outside `[low, high]`, are each
+/// reported.
+pub fn validate_ohlc_impl(data: &[PricePoint]) -> Vec<OhlcError> {
+    let mut errors = Vec::new();
+    for (index, p) in data.iter().enumerate() {
+        let fields = [p.ts, p.open, p.high, p.low, p.close, p.volume];
+        let mut report = |reason| errors.push(OhlcError { index, reason });
+        if !fields.iter().all(|v| v.is_finite()) {
+            report(OhlcIssue::NonFinite);
+            continue;
+        }
+        if p.high < p.low {
+            report(OhlcIssue::HighBelowLow);
+        }
+        if p.open < p.low || p.open > p.high {
+            report(OhlcIssue::OpenOutOfRange);
+        }
+        if p.close < p.low || p.close > p.high {
+            report(OhlcIssue::CloseOutOfRange);
+        }
+    }
+    errors
+}
+
+/// Combined value of `holdings` (symbol, share count) over the union of the
+/// held symbols' timestamps.
+///
+/// Each symbol contributes `shares * close`, forward-filling its last known
+/// close across timestamps it has no bar for. Before a symbol's first bar
+/// (e.g. its IPO), or when `series_by_symbol` has no series for it, it
+/// contributes zero. Series are expected in ascending timestamp order.
+pub fn portfolio_value_impl(
+    holdings: &[(String, f64)],
+    series_by_symbol: &BTreeMap<String, Vec<PricePoint>>,
+) -> Vec<IndicatorPoint> {
+    let held: Vec<(&[PricePoint], f64)> = holdings
+        .iter()
+        .filter_map(|(symbol, shares)| {
+            series_by_symbol
+                .get(symbol)
+                .map(|series| (series.as_slice(), *shares))
+        })
+        .collect();
+
+    let mut timestamps: Vec<f64> = held
+        .iter()
+        .flat_map(|(series, _)| series.iter().map(|p| p.ts))
+        .collect();
+    timestamps.sort_by(f64::total_cmp);
+    timestamps.dedup();
+
+    let mut cursors = vec![0usize; held.len()];
+    timestamps
+        .into_iter()
+        .map(|ts| {
+            let mut value = 0.0;
+            for ((series, shares), cursor) in held.iter().zip(cursors.iter_mut()) {
+                while *cursor < series.len() && series[*cursor].ts <= ts {
+                    *cursor += 1;
+                }
+                if let Some(last) = cursor.checked_sub(1).map(|i| &series[i]) {
+                    value += shares * last.close;
+                }
+            }
+            IndicatorPoint { ts, value }
+        })
+        .collect()
+}
+
+/// Pad `points`, a series that lines up with the tail of `data`, back to
+/// `data.len()` with NaN values stamped at the leading bars, so indices match
+/// the input 1:1.
+fn pad_to_bars(data: &[PricePoint], points: Vec<IndicatorPoint>) -> Vec<IndicatorPoint> {
+    let lead = data.len() - points.len();
+    data[..lead]
+        .iter()
+        .map(|p| IndicatorPoint {
+            ts: p.ts,
+            value: f64::NAN,
+        })
+        .chain(points)
+        .collect()
+}
+
+/// `calc_sma_impl` at full length: one point per bar, NaN during warm-up
+/// (every bar when `period` is 0 or exceeds the data).
+pub fn calc_sma_aligned_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
+    pad_to_bars(data, calc_sma_impl(data, period))
+}
+
+/// `calc_ema_impl` at full length: one point per bar, NaN during warm-up.
+pub fn calc_ema_aligned_impl(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
+    pad_to_bars(data, calc_ema_impl(data, period))
+}
+
+/// Linearly interpolated `pct` percentile (0..=100) of ascending `sorted`.
+fn percentile_of_sorted(sorted: &[f64], pct: f64) -> f64 {
+    let rank = pct / 100.0 * (sorted.len() - 1) as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+/// `pct` percentile of `source` over each trailing `period` window,
+/// timestamped at the window's last bar.
+fn rolling_percentile(
+    data: &[PricePoint],
+    period: usize,
+    pct: f64,
+    source: PriceSource,
+) -> Vec<IndicatorPoint> {
+    if period == 0 || period > data.len() || !(0.0..=100.0).contains(&pct) {
+        return Vec::new();
+    }
+
+    let values: Vec<f64> = data.iter().map(|p| source.value(p)).collect();
+    let mut sorted = Vec::with_capacity(period);
+    (period - 1..data.len())
+        .map(|end| {
+            sorted.clear();
+            sorted.extend_from_slice(&values[end + 1 - period..=end]);
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            IndicatorPoint {
+                ts: data[end].ts,
+                value: percentile_of_sorted(&sorted, pct),
+            }
+        })
+        .collect()
+}
+
+/// Rolling median of `source` over each trailing `period` window (the mean
+/// of the middle two for an even window). Unlike an SMA, a single-bar spike
+/// does not move it.
+pub fn calc_median_filter_impl(
+    data: &[PricePoint],
+    period: usize,
+    source: PriceSource,
+) -> Vec<IndicatorPoint> {
+    rolling_percentile(data, period, 50.0, source)
+}
+
+/// Rolling `pct` percentile (0..=100, linearly interpolated) of closes over
+/// each trailing `period` window. Empty for a `pct` outside 0..=100.
+pub fn calc_percentile_filter_impl(
+    data: &[PricePoint],
+    period: usize,
+    pct: f64,
+) -> Vec<IndicatorPoint> {
+    rolling_percentile(data, period, pct, PriceSource::Close)
+}
+
+/// RSI divergences between consecutive price swings of the same kind.
+///
+/// Swings come from `detect_swings_impl(data, left, right)` and are compared
+/// with the `rsi_period` RSI at the same bars: a higher high in price with a
+/// lower RSI is bearish, a lower low with a higher RSI is bullish. Swings
+/// before the RSI is defined are ignored. Events are ordered by their second
+/// swing.
+pub fn detect_rsi_divergence_impl(
+    data: &[PricePoint],
+    rsi_period: usize,
+    left: usize,
+    right: usize,
+) -> Vec<DivergenceEvent> {
+    let rsi = calc_rsi_impl(data, rsi_period);
+    if rsi.is_empty() {
+        return Vec::new();
+    }
+    // `rsi[j]` belongs to bar `j + rsi_period`.
+    let rsi_at = |index: usize| index.checked_sub(rsi_period).map(|j| rsi[j].value);
+
+    let mut events = Vec::new();
+    let mut last_high: Option<(&Swing, f64)> = None;
+    let mut last_low: Option<(&Swing, f64)> = None;
+    let swings = detect_swings_impl(data, left, right);
+    for swing in &swings {
+        let Some(r) = rsi_at(swing.index) else {
+            continue;
+        };
+        let (prev, kind) = match swing.kind {
+            SwingKind::High => (&mut last_high, DivergenceKind::Bearish),
+            SwingKind::Low => (&mut last_low, DivergenceKind::Bullish),
+        };
+        if let Some((first, first_rsi)) = *prev {
+            let diverges = match kind {
+                DivergenceKind::Bearish => swing.price > first.price && r < first_rsi,
+                DivergenceKind::Bullish => swing.price < first.price && r > first_rsi,
+            };
+            if diverges {
+                events.push(DivergenceEvent {
+                    first_ts: first.ts,
+                    second_ts: swing.ts,
+                    kind,
+                });
+            }
+        }
+        *prev = Some((swing, r));
+    }
+    events
+}
+
+/// For each session in `session_starts` (ascending timestamps, as are the
+/// bars; a session runs until the next start), the high, low and last close
+/// of the most recent earlier session that has bars.
+///
+/// Sessions with no earlier session to refer to, including the first, are
+/// skipped, as are bars before the first start.
+pub fn prior_session_levels_impl(
+    data: &[PricePoint],
+    session_starts: &[f64],
+) -> Vec<SessionLevels> {
+    let mut levels = Vec::new();
+    let mut prior: Option<(f64, f64, f64)> = None;
+    for (k, &start) in session_starts.iter().enumerate() {
+        if let Some((prev_high, prev_low, prev_close)) = prior {
+            levels.push(SessionLevels {
+                session_ts: start,
+                prev_high,
+                prev_low,
+                prev_close,
+            });
+        }
+
+        let end = session_starts.get(k + 1).copied().unwrap_or(f64::INFINITY);
+        let from = data.partition_point(|p| p.ts < start);
+        let to = data.partition_point(|p| p.ts < end);
+        let summary =
+            data[from..to.max(from)]
+                .iter()
+                .fold(None, |acc: Option<(f64, f64, f64)>, p| {
+                    Some(match acc {
+                        Some((high, low, _)) => (high.max(p.high), low.min(p.low), p.close),
+                        None => (p.high, p.low, p.close),
+                    })
+                });
+        if summary.is_some() {
+            prior = summary;
+        }
+    }
+    levels
+}
+
+/// Most bricks `calc_renko_impl` returns; a brick size far below the price
+/// scale would otherwise allocate without bound.
+const MAX_RENKO_BRICKS: usize = 100_000;
+
+/// Classic close-based Renko bricks of `brick_size`, anchored at the first
+/// close.
+///
+/// A new brick forms each time the close moves a full `brick_size` beyond
+/// the last brick's top (up) or bottom (down), so a reversal needs a move of
+/// two bricks from the last close level. One bar can complete several
+/// bricks, all stamped with its timestamp. Brick edges sit at whole multiples
+/// of `brick_size` from the anchor. Non-finite closes are skipped, output
+/// stops at `MAX_RENKO_BRICKS`, and a non-positive size yields nothing.
+pub fn calc_renko_impl(data: &[PricePoint], brick_size: f64) -> Vec<RenkoBrick> {
+    if !brick_size.is_finite() || brick_size <= 0.0 {
+        return Vec::new();
+    }
+    let mut closes = data.iter().filter(|p| p.close.is_finite());
+    let Some(first) = closes.next() else {
+        return Vec::new();
+    };
+
+    let anchor = first.close;
+    let level = |i: i64| anchor + i as f64 * brick_size;
+    let mut bricks = Vec::new();
+    // Brick edges as multiples of `brick_size` from the anchor; counting
+    // bricks directly keeps a close far from the last brick, or a size below
+    // the price's resolution, from looping brick by brick.
+    let (mut lo, mut hi): (i64, i64) = (0, 0);
+    for p in closes {
+        let budget = MAX_RENKO_BRICKS - bricks.len();
+        let up = ((p.close - level(hi)) / brick_size).floor();
+        let down = ((level(lo) - p.close) / brick_size).floor();
+        if up >= 1.0 {
+            let n = (up as usize).min(budget) as i64;
+            bricks.extend((hi..hi + n).map(|i| RenkoBrick {
+                ts: p.ts,
+                open: level(i),
+                close: level(i + 1),
+                up: true,
+            }));
+            (lo, hi) = (hi + n - 1, hi + n);
+        } else if down >= 1.0 {
+            let n = (down as usize).min(budget) as i64;
+            bricks.extend((lo - n + 1..=lo).rev().map(|i| RenkoBrick {
+                ts: p.ts,
+                open: level(i),
+                close: level(i - 1),
+                up: false,
+            }));
+            (lo, hi) = (lo - n, lo - n + 1);
+        }
+        if bricks.len() == MAX_RENKO_BRICKS {
+            break;
+        }
+    }
+    bricks
+}
+
+/// Signed length of the run of same-direction bricks ending at each brick:
+/// +3 for the third up brick in a row, -1 for the first down brick after an
+/// up run.
+pub fn renko_trend_impl(bricks: &[RenkoBrick]) -> Vec<i32> {
+    let mut run: i32 = 0;
+    bricks
+        .iter()
+        .map(|b| {
+            run = match (b.up, run) {
+                (true, r) if r > 0 => r + 1,
+                (true, _) => 1,
+                (false, r) if r < 0 => r - 1,
+                (false, _) => -1,
+            };
+            run
+        })
+        .collect()
+}
+
+/// Timestamps of bricks whose direction differs from the brick before.
+pub fn renko_reversals_impl(bricks: &[RenkoBrick]) -> Vec<f64> {
+    bricks
+        .windows(2)
+        .filter(|w| w[0].up != w[1].up)
+        .map(|w| w[1].ts)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// wasm_bindgen thin wrappers (JsValue <-> native types)
+// ---------------------------------------------------------------------------
+
+#[wasm_bindgen]
+pub fn version() -> String {
+    VERSION.to_string()
+}
+
+#[wasm_bindgen]
+pub fn build_info() -> String {
+    BUILD_INFO.to_string()
+}
+
+/// Route Rust panics to `console.error` (message plus source location)
+/// instead of the opaque "unreachable executed" trap. Idempotent; the data
+/// wrappers below call it lazily so JS does not have to.
+#[wasm_bindgen]
+pub fn init_panic_hook() {
+    console_error_panic_hook::set_once();
+}
+
+#[wasm_bindgen]
+pub fn lttb_downsample(data: JsValue, threshold: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<DataPoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = lttb_downsample_impl(&points, threshold);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_sma(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_sma_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_ema(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_ema_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_rsi(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_rsi_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// RSI with a named smoothing method; unknown names fall back to Wilder.
+#[wasm_bindgen]
+pub fn calc_rsi_method(data: JsValue, period: usize, method: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let method = SmoothingMethod::from_name(method).unwrap_or(SmoothingMethod::Wilder);
+    let result = calc_rsi_smoothed_impl(&points, period, method);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_vwap(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_vwap_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Convert `{ ts, open, high, low, close, volume }` arrays into an array of
+/// bar objects. Throws if the object is malformed or the columns differ in
+/// length.
+#[wasm_bindgen]
+pub fn parse_columnar(obj: JsValue) -> Result<JsValue, JsValue> {
+    init_panic_hook();
+    let cols: OhlcvColumns = serde_wasm_bindgen::from_value(obj)
+        .map_err(|e| JsValue::from_str(&format!("invalid columnar OHLCV: {}", e)))?;
+    let result = columnar_to_prices(&cols).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&result).map_err(JsValue::from)
+}
+
+#[wasm_bindgen]
+pub fn filter_symbols(entries: JsValue, query: JsValue, max_results: f64) -> JsValue {
+    init_panic_hook();
+    let entries: Vec<SymbolEntry> = serde_wasm_bindgen::from_value(entries).unwrap_or_default();
+    let max_results = clamp_max_results(max_results, entries.len());
+    let query: String = serde_wasm_bindgen::from_value(query).unwrap_or_default();
+    let result = filter_symbols_impl(&entries, &query, max_results);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_vwap_from(data: JsValue, anchor_ts: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_vwap_from_impl(&points, anchor_ts);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn aggregate_timeframe(data: JsValue, factor: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = aggregate_timeframe_impl(&points, factor);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn detect_swings(data: JsValue, left: usize, right: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = detect_swings_impl(&points, left, right);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn cluster_levels(swings: JsValue, tolerance_pct: f64) -> JsValue {
+    init_panic_hook();
+    let swings: Vec<Swing> = serde_wasm_bindgen::from_value(swings).unwrap_or_default();
+    let result = cluster_levels_impl(&swings, tolerance_pct);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Percent rank over a named price source; unknown names fall back to close.
+#[wasm_bindgen]
+pub fn calc_percent_rank(data: JsValue, period: usize, source: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let source = PriceSource::from_name(source).unwrap_or(PriceSource::Close);
+    let result = calc_percent_rank_impl(&points, period, source);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn rolling_highest(data: JsValue, period: usize, source: &str) -> Vec<f64> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let source = PriceSource::from_name(source).unwrap_or(PriceSource::High);
+    rolling_highest_impl(&points, period, source)
+        .into_iter()
+        .map(|p| p.value)
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn rolling_lowest(data: JsValue, period: usize, source: &str) -> Vec<f64> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let source = PriceSource::from_name(source).unwrap_or(PriceSource::Low);
+    rolling_lowest_impl(&points, period, source)
+        .into_iter()
+        .map(|p| p.value)
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn bars_since_highest(data: JsValue, period: usize, source: &str) -> Vec<f64> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let source = PriceSource::from_name(source).unwrap_or(PriceSource::High);
+    bars_since_highest_impl(&points, period, source)
+        .into_iter()
+        .map(|p| p.value)
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn bars_since_lowest(data: JsValue, period: usize, source: &str) -> Vec<f64> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let source = PriceSource::from_name(source).unwrap_or(PriceSource::Low);
+    bars_since_lowest_impl(&points, period, source)
+        .into_iter()
+        .map(|p| p.value)
+        .collect()
+}
+
+/// LTTB that keeps the bars at `keep_ts` (a `Float64Array` of timestamps).
+#[wasm_bindgen]
+pub fn lttb_downsample_keep(data: JsValue, threshold: usize, keep_ts: &[f64]) -> JsValue {
+    init_panic_hook();
+    let points: Vec<DataPoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = lttb_downsample_keep_impl(&points, threshold, keep_ts);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// `performance.now()` on the JS global (window or worker): a monotonic
+    /// millisecond clock with sub-millisecond resolution, subject to the
+    /// browser's timer coarsening.
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// Compute a batch of indicators, returning `{ [key]: points }`. When
+/// `precision` is given, values are rounded to that many decimal places.
+#[wasm_bindgen]
+pub fn compute_indicators(data: JsValue, specs: JsValue, precision: Option<u32>) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let specs: Vec<IndicatorSpec> = serde_wasm_bindgen::from_value(specs).unwrap_or_default();
+    let mut result = compute_indicators_impl(&points, &specs);
+    if let Some(decimals) = precision {
+        for series in result.values_mut() {
+            round_to(series, decimals);
+        }
+    }
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    result.serialize(&serializer).unwrap_or(JsValue::NULL)
+}
+
+/// Like `compute_indicators` but returns `{ results, timings }`, where
+/// `timings[key]` is the duration in microseconds measured with
+/// `performance.now()`.
+#[wasm_bindgen]
+pub fn compute_indicators_timed(data: JsValue, specs: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let specs: Vec<IndicatorSpec> = serde_wasm_bindgen::from_value(specs).unwrap_or_default();
+    let result = compute_indicators_timed_impl(&points, &specs, performance_now);
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    result.serialize(&serializer).unwrap_or(JsValue::NULL)
+}
+
+/// A symbol universe parsed once and queried per keystroke, so the hot path
+/// never re-deserializes the entry list from JS.
+///
+/// Lowercased symbols are also kept in sorted order so exact and prefix
+/// matches can be located by binary search. When those alone fill
+/// `max_results` they necessarily outrank every contains / name match, so
+/// the linear scan is skipped; otherwise the query falls back to
+/// `filter_symbols_impl` over the full universe. Either way the output is
+/// identical to `filter_symbols_impl`.
+///
+/// Only the first case is faster than a plain scan. A rare prefix, a name
+/// query, or a `max_results` above the prefix hit count still has to look
+/// for contains / name matches in every entry, so it costs the same as
+/// `filter_symbols_impl` (see `benches/symbol_search.rs`).
+#[wasm_bindgen]
+pub struct SymbolIndex {
+    entries: Vec<SymbolEntry>,
+    /// `(lowercased symbol, index into entries)`, sorted by symbol.
+    by_symbol: Vec<(String, usize)>,
+}
+
+impl SymbolIndex {
+    /// Build an index from already-parsed entries.
+    pub fn from_entries(entries: Vec<SymbolEntry>) -> SymbolIndex {
+        let mut by_symbol: Vec<(String, usize)> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.symbol.to_lowercase(), i))
+            .collect();
+        by_symbol.sort();
+        SymbolIndex { entries, by_symbol }
+    }
+
+    /// Native counterpart of `filter`.
+    pub fn search(&self, query: &str, max_results: usize) -> Vec<SymbolEntry> {
+        let q = query.trim().to_lowercase();
+        if !q.is_empty() && max_results > 0 {
+            let start = self
+                .by_symbol
+                .partition_point(|(s, _)| s.as_str() < q.as_str());
+            let matches = self.by_symbol[start..]
+                .iter()
+                .take_while(|(s, _)| s.starts_with(&q))
+                .count();
+
+            if matches >= max_results {
+                // Restore input order so score ties break exactly as in the
+                // full scan.
+                let mut indices: Vec<usize> = self.by_symbol[start..start + matches]
+                    .iter()
+                    .map(|(_, i)| *i)
+                    .collect();
+                indices.sort_unstable();
+                let candidates: Vec<SymbolEntry> = indices
+                    .into_iter()
+                    .map(|i| self.entries[i].clone())
+                    .collect();
+                return filter_symbols_impl(&candidates, query, max_results);
+            }
+        }
+
+        filter_symbols_impl(&self.entries, query, max_results)
+    }
+}
+
+#[wasm_bindgen]
+impl SymbolIndex {
+    #[wasm_bindgen(constructor)]
+    pub fn new(entries: JsValue) -> SymbolIndex {
+        init_panic_hook();
+        let entries: Vec<SymbolEntry> = serde_wasm_bindgen::from_value(entries).unwrap_or_default();
+        SymbolIndex::from_entries(entries)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[wasm_bindgen(js_name = isEmpty)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn filter(&self, query: &str, max_results: f64) -> JsValue {
+        let max_results = clamp_max_results(max_results, self.entries.len());
+        let result = self.search(query, max_results);
+        serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+    }
+}
+
+#[wasm_bindgen]
+pub fn calc_klinger(data: JsValue, fast: usize, slow: usize, signal: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_klinger_impl(&points, fast, slow, signal);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_fisher(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_fisher_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_bop(data: JsValue, smoothing: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_bop_impl(&points, smoothing);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Candlestick pattern scan. `thresholds` may be `undefined` or a partial
+/// `PatternThresholds` object; missing fields use the defaults.
+#[wasm_bindgen]
+pub fn detect_patterns(data: JsValue, thresholds: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let thresholds: PatternThresholds =
+        serde_wasm_bindgen::from_value(thresholds).unwrap_or_default();
+    let result = detect_patterns_with_impl(&points, &thresholds);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn nz_values(values: &[f64], default: f64) -> Vec<f64> {
+    init_panic_hook();
+    values.iter().map(|v| nz(*v, default)).collect()
+}
+
+#[wasm_bindgen]
+pub fn change(values: &[f64], n: usize) -> Vec<f64> {
+    init_panic_hook();
+    change_impl(values, n)
+}
+
+/// `conditions` is a `Uint8Array` where any non-zero byte counts as true.
+#[wasm_bindgen]
+pub fn valuewhen(conditions: &[u8], values: &[f64], occurrence: usize) -> Vec<f64> {
+    init_panic_hook();
+    let conditions: Vec<bool> = conditions.iter().map(|c| *c != 0).collect();
+    valuewhen_impl(&conditions, values, occurrence)
+}
+
+/// Symbol search returning `{ results, total }` for "showing N of M" UIs.
+#[wasm_bindgen]
+pub fn filter_symbols_counted(entries: JsValue, query: JsValue, max_results: f64) -> JsValue {
+    init_panic_hook();
+    let entries: Vec<SymbolEntry> = serde_wasm_bindgen::from_value(entries).unwrap_or_default();
+    let max_results = clamp_max_results(max_results, entries.len());
+    let query: String = serde_wasm_bindgen::from_value(query).unwrap_or_default();
+    let (results, total) = filter_symbols_counted_impl(&entries, &query, max_results);
+    serde_wasm_bindgen::to_value(&CountedSymbols { results, total }).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_ultimate(data: JsValue, short: usize, medium: usize, long: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_ultimate_impl(&points, short, medium, long);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_mass_index(data: JsValue, ema_period: usize, sum_period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_mass_index_impl(&points, ema_period, sum_period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_nvi(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_nvi_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_pvi(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_pvi_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_adl(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_adl_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_chaikin_osc(data: JsValue, fast: usize, slow: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_chaikin_osc_impl(&points, fast, slow);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_elder_ray(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_elder_ray_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn scale_timestamps(data: JsValue, factor: f64) -> JsValue {
+    init_panic_hook();
+    let mut points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    scale_timestamps_impl(&mut points, factor);
+    serde_wasm_bindgen::to_value(&points).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn normalize_timestamps(data: JsValue, to_millis: bool) -> JsValue {
+    init_panic_hook();
+    let mut points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    normalize_ts_impl(&mut points, to_millis);
+    serde_wasm_bindgen::to_value(&points).unwrap_or(JsValue::NULL)
+}
+
+/// `"seconds"` or `"millis"`.
+#[wasm_bindgen(js_name = detect_ts_unit)]
+pub fn detect_ts_unit_js(data: JsValue) -> String {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    match detect_ts_unit(&points) {
+        TsUnit::Seconds => "seconds".to_string(),
+        TsUnit::Millis => "millis".to_string(),
+    }
+}
+
+#[wasm_bindgen]
+pub fn calc_vwap_skip_zero(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_vwap_skip_zero_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Incremental, approximate LTTB for append-only series.
+///
+/// Points are grouped into buckets of `bucket_size` raw points; as soon as
+/// a bucket fills, the point forming the largest triangle with the
+/// previously kept point and the bucket's own mean is frozen. Once the
+/// frozen set would exceed the budget, adjacent pairs are merged (keeping
+/// the larger-triangle point of each pair) and `bucket_size` doubles, so
+/// each push is amortised O(1) and `snapshot` is O(threshold).
+///
+/// Tradeoff versus batch LTTB: a bucket is chosen before the following
+/// bucket exists, so the look-ahead uses the bucket's own mean instead of
+/// the next bucket's, and older regions are thinned by pairwise merging
+/// rather than a global re-bucketing. The shape stays close but the picked
+/// indices differ. Up to `threshold` points the snapshot is exactly the
+/// input; the first and latest points are always kept, and the snapshot
+/// never exceeds `threshold` (clamped to at least 3).
+#[wasm_bindgen]
+pub struct LttbStream {
+    threshold: usize,
+    bucket_size: usize,
+    count: usize,
+    first: Option<DataPoint>,
+    last: Option<DataPoint>,
+    frozen: Vec<DataPoint>,
+    current: Vec<DataPoint>,
+}
+
+impl LttbStream {
+    /// Append one point; timestamps are assumed non-decreasing.
+    pub fn push(&mut self, point: DataPoint) {
+        self.count += 1;
+        if self.first.is_none() {
+            self.first = Some(point);
+            return;
+        }
+        if let Some(previous) = self.last.replace(point) {
+            self.feed(previous);
+        }
+    }
+
+    /// Current downsampled view, sorted by ts.
+    pub fn snapshot(&self) -> Vec<DataPoint> {
+        let mut out: Vec<DataPoint> = Vec::with_capacity(self.threshold);
+        out.extend(self.first.iter().cloned());
+        out.extend(self.frozen.iter().cloned());
+        if !self.current.is_empty() {
+            out.push(self.pick(&self.current));
+        }
+        out.extend(self.last.iter().cloned());
+        out
+    }
+
+    fn anchor(&self) -> &DataPoint {
+        self.frozen
+            .last()
+            .or(self.first.as_ref())
+            .expect("feed is only reached after the first point")
+    }
+
+    /// Largest-triangle point of `bucket` relative to the last kept point
+    /// and the bucket's mean.
+    fn pick(&self, bucket: &[DataPoint]) -> DataPoint {
+        let anchor = self.anchor();
+        let avg = lttb_mean_offset(anchor, bucket);
+
+        let mut best = &bucket[0];
+        let mut best_area = -1.0;
+        for p in bucket {
+            let area = lttb_area(anchor, p, avg);
+            if area > best_area {
+                best_area = area;
+                best = p;
+            }
+        }
+        best.clone()
+    }
+
+    fn feed(&mut self, point: DataPoint) {
+        // Budget: first + frozen + in-progress pick + last <= threshold.
+        if self.current.is_empty() && self.frozen.len() >= self.threshold - 2 {
+            self.compact();
+        }
+
+        self.current.push(point);
+        if self.current.len() == self.bucket_size {
+            let chosen = self.pick(&self.current);
+            self.frozen.push(chosen);
+            self.current.clear();
+        }
+    }
+
+    /// Halve the frozen set by keeping the larger-triangle point of each
+    /// adjacent pair, and double the bucket size to match. An unpaired last
+    /// point seeds the next bucket instead of staying frozen: at threshold 3
+    /// the budget has no room for a frozen point next to an in-progress pick.
+    fn compact(&mut self) {
+        let frozen = std::mem::take(&mut self.frozen);
+        let mut kept: Vec<DataPoint> = Vec::with_capacity(frozen.len() / 2);
+
+        for (i, pair) in frozen.chunks(2).enumerate() {
+            if pair.len() == 1 {
+                self.current.push(pair[0].clone());
+                continue;
+            }
+            let prev = kept.last().or(self.first.as_ref()).unwrap_or(&pair[0]);
+            let next = frozen
+                .get(2 * i + 2)
+                .or(self.last.as_ref())
+                .unwrap_or(&pair[1]);
+            let offset = (next.ts - prev.ts, next.value - prev.value);
+            let a0 = lttb_area(prev, &pair[0], offset);
+            let a1 = lttb_area(prev, &pair[1], offset);
+            kept.push(if a1 > a0 {
+                pair[1].clone()
+            } else {
+                pair[0].clone()
+            });
+        }
+
+        self.frozen = kept;
+        self.bucket_size *= 2;
+    }
+}
+
+#[wasm_bindgen]
+impl LttbStream {
+    #[wasm_bindgen(constructor)]
+    pub fn new(threshold: usize) -> LttbStream {
+        init_panic_hook();
+        LttbStream {
+            threshold: threshold.max(3),
+            bucket_size: 1,
+            count: 0,
+            first: None,
+            last: None,
+            frozen: Vec::new(),
+            current: Vec::new(),
+        }
+    }
+
+    /// Number of raw points pushed so far.
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    #[wasm_bindgen(js_name = push)]
+    pub fn push_js(&mut self, ts: f64, value: f64) {
+        self.push(DataPoint { ts, value });
+    }
+
+    #[wasm_bindgen(js_name = snapshot)]
+    pub fn snapshot_js(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.snapshot()).unwrap_or(JsValue::NULL)
+    }
+}
+
+#[wasm_bindgen]
+pub fn calc_sma_range(data: JsValue, period: usize, start_idx: usize, end_idx: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_sma_range_impl(&points, period, start_idx, end_idx);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn find_gaps(data: JsValue, expected_interval_ms: f64, tolerance: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = find_gaps_impl(&points, expected_interval_ms, tolerance);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn detect_outliers(data: JsValue, window: usize, z_threshold: f64) -> Vec<u32> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    detect_outliers_impl(&points, window, z_threshold)
+        .into_iter()
+        .map(|i| i as u32)
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn clean_outliers(data: JsValue, window: usize, z_threshold: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = clean_outliers_impl(&points, window, z_threshold);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_ema_seeded(data: JsValue, period: usize, seed: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let seed = EmaSeed::from_name(seed).unwrap_or_default();
+    let result = calc_ema_seeded_impl(&points, period, seed);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_ma_distance(data: JsValue, period: usize, ma_kind: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let ma_kind = MaKind::from_name(ma_kind).unwrap_or_default();
+    let result = calc_ma_distance_impl(&points, period, ma_kind);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_rolling_vwap(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_rolling_vwap_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Evaluate an indicator expression (see `eval_expression_impl`). Throws on
+/// a parse error.
+#[wasm_bindgen]
+pub fn eval_expression(data: JsValue, expr: &str) -> Result<JsValue, JsValue> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = eval_expression_impl(&points, expr).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&result).map_err(JsValue::from)
+}
+
+#[wasm_bindgen]
+pub fn normalize_series(points: JsValue, mode: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<IndicatorPoint> = serde_wasm_bindgen::from_value(points).unwrap_or_default();
+    let mode = NormMode::from_name(mode).unwrap_or_default();
+    let result = normalize_series_impl(&points, mode);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn ensure_ascending(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = ensure_ascending_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn align_closes(a: JsValue, b: JsValue) -> JsValue {
+    init_panic_hook();
+    let a: Vec<PricePoint> = serde_wasm_bindgen::from_value(a).unwrap_or_default();
+    let b: Vec<PricePoint> = serde_wasm_bindgen::from_value(b).unwrap_or_default();
+    let result = align_closes_impl(&a, &b);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_ratio(a: JsValue, b: JsValue) -> JsValue {
+    init_panic_hook();
+    let a: Vec<PricePoint> = serde_wasm_bindgen::from_value(a).unwrap_or_default();
+    let b: Vec<PricePoint> = serde_wasm_bindgen::from_value(b).unwrap_or_default();
+    let result = calc_ratio_impl(&a, &b);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_spread(a: JsValue, b: JsValue, hedge_ratio: f64) -> JsValue {
+    init_panic_hook();
+    let a: Vec<PricePoint> = serde_wasm_bindgen::from_value(a).unwrap_or_default();
+    let b: Vec<PricePoint> = serde_wasm_bindgen::from_value(b).unwrap_or_default();
+    let result = calc_spread_impl(&a, &b, hedge_ratio);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_relative_performance(asset: JsValue, benchmark: JsValue) -> JsValue {
+    init_panic_hook();
+    let asset: Vec<PricePoint> = serde_wasm_bindgen::from_value(asset).unwrap_or_default();
+    let benchmark: Vec<PricePoint> = serde_wasm_bindgen::from_value(benchmark).unwrap_or_default();
+    let result = calc_relative_performance_impl(&asset, &benchmark);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_zscore(points: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<IndicatorPoint> = serde_wasm_bindgen::from_value(points).unwrap_or_default();
+    let result = calc_zscore_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn uniform_sample(data: JsValue, count: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<DataPoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = uniform_sample_impl(&points, count);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Serialize with `i64` timestamps emitted as JS `BigInt`s.
+fn to_value_bigint<T: Serialize>(value: &T) -> JsValue {
+    let serializer =
+        serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);
+    value.serialize(&serializer).unwrap_or(JsValue::NULL)
+}
+
+/// LTTB taking timestamps as a `BigInt64Array`; returns `{ ts: bigint,
+/// value }` objects.
+#[wasm_bindgen]
+pub fn lttb_downsample_i64(ts: &[i64], values: &[f64], threshold: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<DataPointI> = ts
+        .iter()
+        .zip(values)
+        .map(|(&ts, &value)| DataPointI { ts, value })
+        .collect();
+    to_value_bigint(&lttb_downsample_i_impl(&points, threshold))
+}
+
+/// SMA taking timestamps as a `BigInt64Array` alongside closes; returns
+/// `{ ts: bigint, value }` objects.
+#[wasm_bindgen]
+pub fn calc_sma_i64(ts: &[i64], closes: &[f64], period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePointI> = ts
+        .iter()
+        .zip(closes)
+        .map(|(&ts, &close)| PricePointI {
+            ts,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 0.0,
+        })
+        .collect();
+    to_value_bigint(&calc_sma_i_impl(&points, period))
+}
+
+#[wasm_bindgen]
+pub fn dedupe_timestamps(data: JsValue, keep: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let keep = DedupePolicy::from_name(keep).unwrap_or_default();
+    let result = dedupe_timestamps_impl(&points, keep);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_sma_multi(data: JsValue, periods: &[u32]) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let periods: Vec<usize> = periods.iter().map(|&p| p as usize).collect();
+    let result = calc_sma_multi_impl(&points, &periods);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn detect_ma_cross(data: JsValue, fast: usize, slow: usize, ma_kind: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let ma_kind = MaKind::from_name(ma_kind).unwrap_or_default();
+    let result = detect_ma_cross_impl(&points, fast, slow, ma_kind);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_volume_profile(data: JsValue, num_bins: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_volume_profile_impl(&points, num_bins);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Value Area of a profile from `calc_volume_profile`; `fraction` defaults
+/// to 0.70.
+#[wasm_bindgen]
+pub fn calc_value_area(profile: JsValue, fraction: Option<f64>) -> JsValue {
+    init_panic_hook();
+    let profile: VolumeProfile = serde_wasm_bindgen::from_value(profile).unwrap_or_default();
+    let result = calc_value_area_impl(&profile, fraction.unwrap_or(0.70));
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_multi_horizon_change(data: JsValue, horizons: &[u32]) -> Vec<f64> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let horizons: Vec<usize> = horizons.iter().map(|&h| h as usize).collect();
+    calc_multi_horizon_change_impl(&points, &horizons)
+}
+
+#[wasm_bindgen]
+pub fn calc_bollinger(data: JsValue, period: usize, num_std: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_bollinger_impl(&points, period, num_std);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_bollinger_pctb(data: JsValue, period: usize, num_std: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_bollinger_pctb_impl(&points, period, num_std);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_bollinger_bandwidth(data: JsValue, period: usize, num_std: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_bollinger_bandwidth_impl(&points, period, num_std);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_atr(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_atr_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_keltner(data: JsValue, ema_period: usize, atr_period: usize, mult: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_keltner_impl(&points, ema_period, atr_period, mult);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn detect_squeeze(
+    data: JsValue,
+    bb_period: usize,
+    bb_std: f64,
+    kc_ema: usize,
+    kc_atr: usize,
+    kc_mult: f64,
+) -> Vec<u8> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    detect_squeeze_impl(&points, bb_period, bb_std, kc_ema, kc_atr, kc_mult)
+        .into_iter()
+        .map(u8::from)
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn filter_symbols_mode(
+    entries: JsValue,
+    query: JsValue,
+    max_results: f64,
+    mode: &str,
+) -> JsValue {
+    init_panic_hook();
+    let entries: Vec<SymbolEntry> = serde_wasm_bindgen::from_value(entries).unwrap_or_default();
+    let max_results = clamp_max_results(max_results, entries.len());
+    let query: String = serde_wasm_bindgen::from_value(query).unwrap_or_default();
+    let mode = MatchMode::from_name(mode).unwrap_or_default();
+    let result = filter_symbols_mode_impl(&entries, &query, max_results, mode);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Cumulative VWAP updated one bar at a time for live sessions. Produces
+/// the same values as `calc_vwap_impl` over the bars pushed since the last
+/// `reset`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct VwapState {
+    cum_tp_vol: f64,
+    cum_vol: f64,
+}
+
+impl VwapState {
+    /// Accumulate one bar and return the updated VWAP.
+    pub fn push_bar(&mut self, bar: &PricePoint) -> f64 {
+        let typical_price = (bar.high + bar.low + bar.close) / 3.0;
+        self.cum_tp_vol += typical_price * bar.volume;
+        self.cum_vol += bar.volume;
+        self.value()
+    }
+}
+
+#[wasm_bindgen]
+impl VwapState {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> VwapState {
+        init_panic_hook();
+        VwapState::default()
+    }
+
+    /// Accumulate one `PricePoint`-shaped bar; a malformed bar is ignored.
+    pub fn push(&mut self, bar: JsValue) -> f64 {
+        match serde_wasm_bindgen::from_value::<PricePoint>(bar) {
+            Ok(bar) => self.push_bar(&bar),
+            Err(_) => self.value(),
+        }
+    }
+
+    /// Start a new session.
+    pub fn reset(&mut self) {
+        *self = VwapState::default();
+    }
+
+    /// Current VWAP, or 0 before any volume has traded.
+    pub fn value(&self) -> f64 {
+        if self.cum_vol == 0.0 {
+            0.0
+        } else {
+            self.cum_tp_vol / self.cum_vol
+        }
+    }
+}
+
+/// Simple moving average updated one close at a time for live ticks. Keeps
+/// the last `period` closes and a running sum, so each push is O(1), and
+/// emits the same values as `calc_sma_impl` over the same closes.
+#[wasm_bindgen]
+pub struct SmaState {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+#[wasm_bindgen]
+impl SmaState {
+    #[wasm_bindgen(constructor)]
+    pub fn new(period: usize) -> SmaState {
+        init_panic_hook();
+        // `period` comes straight from JS; let the window grow as closes
+        // arrive instead of reserving it up front.
+        SmaState {
+            period,
+            window: VecDeque::new(),
+            sum: 0.0,
+        }
+    }
+
+    /// Add one close and return the updated SMA, or `None` until `period`
+    /// closes have been seen.
+    pub fn push(&mut self, close: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        if self.window.len() == self.period {
+            let oldest = self.window.pop_front().unwrap_or_default();
+            self.sum += close - oldest;
+        } else {
+            self.sum += close;
+        }
+        self.window.push_back(close);
+        self.value()
+    }
+
+    /// Current SMA, or `None` while the window is still filling.
+    pub fn value(&self) -> Option<f64> {
+        if self.period == 0 || self.window.len() < self.period {
+            None
+        } else {
+            Some(self.sum / self.period as f64)
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub fn calc_price_series(data: JsValue, source: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let source = PriceSource::from_name(source).unwrap_or(PriceSource::Close);
+    let result = calc_price_series_impl(&points, source);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn to_log_space(points: JsValue, base: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<IndicatorPoint> = serde_wasm_bindgen::from_value(points).unwrap_or_default();
+    let result = to_log_space_impl(&points, base);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn from_log_space(points: JsValue, base: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<IndicatorPoint> = serde_wasm_bindgen::from_value(points).unwrap_or_default();
+    let result = from_log_space_impl(&points, base);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn lttb_downsample_indices(data: JsValue, threshold: usize) -> Vec<u32> {
+    init_panic_hook();
+    let points: Vec<DataPoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    lttb_downsample_indices_impl(&points, threshold)
+        .into_iter()
+        .map(|i| i as u32)
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn position_size(account_equity: f64, risk_pct: f64, entry: f64, stop: f64) -> f64 {
+    position_size_impl(account_equity, risk_pct, entry, stop)
+}
+
+#[wasm_bindgen]
+pub fn position_size_atr(account_equity: f64, risk_pct: f64, atr: f64, atr_mult: f64) -> f64 {
+    position_size_atr_impl(account_equity, risk_pct, atr, atr_mult)
+}
+
+#[wasm_bindgen]
+pub fn backtest_cross(data: JsValue, fast: usize, slow: usize, ma_kind: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let ma_kind = MaKind::from_name(ma_kind).unwrap_or_default();
+    let result = backtest_cross_impl(&points, fast, slow, ma_kind);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_drawdown(data: JsValue) -> f64 {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    calc_drawdown_impl(&points)
+}
+
+#[wasm_bindgen]
+pub fn calc_rolling_drawdown(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_rolling_drawdown_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn backtest_signals(data: JsValue, signals: &[i32]) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = backtest_signals_impl(&points, signals);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_returns(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_returns_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_cumulative_return(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_cumulative_return_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_rolling_return(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_rolling_return_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn rebase_series(data: JsValue, base: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = rebase_series_impl(&points, base);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Takes an array of bar arrays; returns one rebased series per input.
+#[wasm_bindgen]
+pub fn rebase_multi(series: JsValue, base: f64) -> JsValue {
+    init_panic_hook();
+    let series: Vec<Vec<PricePoint>> = serde_wasm_bindgen::from_value(series).unwrap_or_default();
+    let result = rebase_multi_impl(&series, base);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_sharpe(returns: &[f64], risk_free: f64, periods_per_year: f64) -> f64 {
+    calc_sharpe_impl(returns, risk_free, periods_per_year)
+}
+
+#[wasm_bindgen]
+pub fn calc_sortino(returns: &[f64], risk_free: f64, periods_per_year: f64) -> f64 {
+    calc_sortino_impl(returns, risk_free, periods_per_year)
+}
+
+#[wasm_bindgen]
+pub fn summary_stats(data: JsValue, periods_per_year: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = summary_stats_impl(&points, periods_per_year);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_psar(data: JsValue, step: f64, max_step: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_psar_impl(&points, step, max_step);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn psar_signals(data: JsValue, step: f64, max_step: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = psar_signals_impl(&points, step, max_step);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Merge an array of signal arrays into one chronological stream.
+#[wasm_bindgen]
+pub fn merge_signals(streams: JsValue) -> JsValue {
+    init_panic_hook();
+    let streams: Vec<Vec<Signal>> = serde_wasm_bindgen::from_value(streams).unwrap_or_default();
+    let result = merge_signals_impl(&streams);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_atr_stop(data: JsValue, atr_period: usize, multiplier: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_atr_stop_impl(&points, atr_period, multiplier);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Warm-up bar count for `indicator`, or `undefined` if it is unknown.
+#[wasm_bindgen]
+pub fn warmup_bars(indicator: &str, period: usize) -> Option<usize> {
+    warmup_bars_impl(indicator, period)
+}
+
+/// Takes an array of `[source, entries]` pairs.
+#[wasm_bindgen]
+pub fn filter_symbols_multi(universes: JsValue, query: JsValue, max_results: f64) -> JsValue {
+    init_panic_hook();
+    let universes: Vec<(String, Vec<SymbolEntry>)> =
+        serde_wasm_bindgen::from_value(universes).unwrap_or_default();
+    let total = universes.iter().map(|(_, entries)| entries.len()).sum();
+    let max_results = clamp_max_results(max_results, total);
+    let query: String = serde_wasm_bindgen::from_value(query).unwrap_or_default();
+    let result = filter_symbols_multi_impl(&universes, &query, max_results);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_rvol(data: JsValue, avg_period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_rvol_impl(&points, avg_period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn lttb_downsample_smoothed(data: JsValue, threshold: usize, smooth_window: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<DataPoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = lttb_downsample_smoothed_impl(&points, threshold, smooth_window);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_envelope(data: JsValue, period: usize, pct: f64, ma_kind: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let ma_kind = MaKind::from_name(ma_kind).unwrap_or_default();
+    let result = calc_envelope_impl(&points, period, pct, ma_kind);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn detect_price_gaps(data: JsValue, min_pct: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = detect_price_gaps_impl(&points, min_pct);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_beta(asset: JsValue, benchmark: JsValue) -> f64 {
+    init_panic_hook();
+    let asset: Vec<PricePoint> = serde_wasm_bindgen::from_value(asset).unwrap_or_default();
+    let benchmark: Vec<PricePoint> = serde_wasm_bindgen::from_value(benchmark).unwrap_or_default();
+    calc_beta_impl(&asset, &benchmark)
+}
+
+#[wasm_bindgen]
+pub fn calc_rolling_beta(asset: JsValue, benchmark: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let asset: Vec<PricePoint> = serde_wasm_bindgen::from_value(asset).unwrap_or_default();
+    let benchmark: Vec<PricePoint> = serde_wasm_bindgen::from_value(benchmark).unwrap_or_default();
+    let result = calc_rolling_beta_impl(&asset, &benchmark, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn lttb_downsample_time(data: JsValue, threshold: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<DataPoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = lttb_downsample_time_impl(&points, threshold);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Returned as an `Int8Array` of +1 / -1.
+#[wasm_bindgen]
+pub fn candle_directions(data: JsValue) -> Vec<i8> {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    candle_directions_impl(&points)
+        .into_iter()
+        .map(|d| d as i8)
+        .collect()
+}
+
+/// Build `{ ts: Float64Array, value: Float64Array }` directly, without going
+/// through serde, so each column is a single typed-array copy.
+fn indicator_columns_to_js(points: &[IndicatorPoint]) -> JsValue {
+    let cols = to_indicator_columns_impl(points);
+    let obj = js_sys::Object::new();
+    let ts = js_sys::Float64Array::from(cols.ts.as_slice());
+    let value = js_sys::Float64Array::from(cols.value.as_slice());
+    if js_sys::Reflect::set(&obj, &"ts".into(), &ts).is_err()
+        || js_sys::Reflect::set(&obj, &"value".into(), &value).is_err()
+    {
+        return JsValue::NULL;
+    }
+    obj.into()
+}
+
+/// Convert an array of `{ts, value}` points (any indicator's output) to
+/// columnar form.
+#[wasm_bindgen]
+pub fn to_columnar(points: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<IndicatorPoint> = serde_wasm_bindgen::from_value(points).unwrap_or_default();
+    indicator_columns_to_js(&points)
+}
+
+/// `calc_sma` returning `{ ts: Float64Array, value: Float64Array }`.
+#[wasm_bindgen]
+pub fn calc_sma_columnar(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    indicator_columns_to_js(&calc_sma_impl(&points, period))
+}
+
+/// `calc_ema` returning `{ ts: Float64Array, value: Float64Array }`.
+#[wasm_bindgen]
+pub fn calc_ema_columnar(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    indicator_columns_to_js(&calc_ema_impl(&points, period))
+}
+
+/// `calc_rsi` returning `{ ts: Float64Array, value: Float64Array }`.
+#[wasm_bindgen]
+pub fn calc_rsi_columnar(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    indicator_columns_to_js(&calc_rsi_impl(&points, period))
+}
+
+/// `calc_vwap` returning `{ ts: Float64Array, value: Float64Array }`.
+#[wasm_bindgen]
+pub fn calc_vwap_columnar(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    indicator_columns_to_js(&calc_vwap_impl(&points))
+}
+
+#[wasm_bindgen]
+pub fn calc_stc(data: JsValue, fast: usize, slow: usize, cycle: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_stc_impl(&points, fast, slow, cycle);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_connors_rsi(
+    data: JsValue,
+    rsi_period: usize,
+    streak_rsi_period: usize,
+    pct_rank_period: usize,
+) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_connors_rsi_impl(&points, rsi_period, streak_rsi_period, pct_rank_period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_tsi(data: JsValue, long: usize, short: usize, signal: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_tsi_impl(&points, long, short, signal);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn describe_series(points: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<IndicatorPoint> = serde_wasm_bindgen::from_value(points).unwrap_or_default();
+    let result = describe_series_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn validate_ohlc(data: JsValue) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = validate_ohlc_impl(&points);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// `holdings` is an array of `[symbol, shares]` pairs and `series_by_symbol`
+/// an object mapping each symbol to its bars.
+#[wasm_bindgen]
+pub fn portfolio_value(holdings: JsValue, series_by_symbol: JsValue) -> JsValue {
+    init_panic_hook();
+    let holdings: Vec<(String, f64)> = serde_wasm_bindgen::from_value(holdings).unwrap_or_default();
+    let series: BTreeMap<String, Vec<PricePoint>> =
+        serde_wasm_bindgen::from_value(series_by_symbol).unwrap_or_default();
+    let result = portfolio_value_impl(&holdings, &series);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_sma_aligned(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_sma_aligned_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_ema_aligned(data: JsValue, period: usize) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_ema_aligned_impl(&points, period);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_median_filter(data: JsValue, period: usize, source: &str) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let source = PriceSource::from_name(source).unwrap_or(PriceSource::Close);
+    let result = calc_median_filter_impl(&points, period, source);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_percentile_filter(data: JsValue, period: usize, pct: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_percentile_filter_impl(&points, period, pct);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn detect_rsi_divergence(
+    data: JsValue,
+    rsi_period: usize,
+    left: usize,
+    right: usize,
+) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = detect_rsi_divergence_impl(&points, rsi_period, left, right);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_ema_halflife(data: JsValue, half_life: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_ema_halflife_impl(&points, half_life);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn prior_session_levels(data: JsValue, session_starts: &[f64]) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = prior_session_levels_impl(&points, session_starts);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Takes an array of bar arrays and one `IndicatorSpec`.
+#[wasm_bindgen]
+pub fn compute_many(symbols: JsValue, spec: JsValue) -> JsValue {
+    init_panic_hook();
+    let symbols: Vec<Vec<PricePoint>> = serde_wasm_bindgen::from_value(symbols).unwrap_or_default();
+    let Ok(spec) = serde_wasm_bindgen::from_value::<IndicatorSpec>(spec) else {
+        return JsValue::NULL;
+    };
+    let result = compute_many_impl(&symbols, &spec);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn filter_symbols_min(
+    entries: JsValue,
+    query: JsValue,
+    max_results: f64,
+    min_score: i32,
+) -> JsValue {
+    init_panic_hook();
+    let entries: Vec<SymbolEntry> = serde_wasm_bindgen::from_value(entries).unwrap_or_default();
+    let max_results = clamp_max_results(max_results, entries.len());
+    let query: String = serde_wasm_bindgen::from_value(query).unwrap_or_default();
+    let result = filter_symbols_min_impl(&entries, &query, max_results, min_score);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+#[wasm_bindgen]
+pub fn calc_renko(data: JsValue, brick_size: f64) -> JsValue {
+    init_panic_hook();
+    let points: Vec<PricePoint> = serde_wasm_bindgen::from_value(data).unwrap_or_default();
+    let result = calc_renko_impl(&points, brick_size);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Takes the bricks from `calc_renko`; returns an `Int32Array`.
+#[wasm_bindgen]
+pub fn renko_trend(bricks: JsValue) -> Vec<i32> {
+    init_panic_hook();
+    let bricks: Vec<RenkoBrick> = serde_wasm_bindgen::from_value(bricks).unwrap_or_default();
+    renko_trend_impl(&bricks)
+}
+
+/// Takes the bricks from `calc_renko`; returns a `Float64Array` of
+/// timestamps.
+#[wasm_bindgen]
+pub fn renko_reversals(bricks: JsValue) -> Vec<f64> {
+    init_panic_hook();
+    let bricks: Vec<RenkoBrick> = serde_wasm_bindgen::from_value(bricks).unwrap_or_default();
+    renko_reversals_impl(&bricks)
+}
+
+// ---------------------------------------------------------------------------
+// Unit tests (pure Rust — no JsValue required)
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // -----------------------------------------------------------------------
+    // Helpers
+    // -----------------------------------------------------------------------
+
+    fn dp(ts: f64, value: f64) -> DataPoint {
+        DataPoint { ts, value }
+    }
+
+    fn pp(ts: f64, open: f64, high: f64, low: f64, close: f64, volume: f64) -> PricePoint {
+        PricePoint {
+            ts,
+            open,
+            high,
+            low,
+            close,
+            volume,
+        }
+    }
+
+    fn sample_prices() -> Vec<PricePoint> {
+        vec![
+            pp(1.0, 10.0, 12.0, 9.0, 11.0, 100.0),
+            pp(2.0, 11.0, 13.0, 10.0, 12.0, 150.0),
+            pp(3.0, 12.0, 14.0, 11.0, 13.0, 200.0),
+            pp(4.0, 13.0, 15.0, 12.0, 14.0, 120.0),
+            pp(5.0, 14.0, 16.0, 13.0, 15.0, 180.0),
+            pp(6.0, 15.0, 17.0, 14.0, 14.0, 160.0),
+            pp(7.0, 14.0, 15.0, 12.0, 13.0, 140.0),
+            pp(8.0, 13.0, 14.0, 11.0, 12.0, 130.0),
+            pp(9.0, 12.0, 13.0, 10.0, 11.0, 110.0),
+            pp(10.0, 11.0, 12.0, 9.0, 10.0, 100.0),
+        ]
+    }
+
+    // -----------------------------------------------------------------------
+    // LTTB downsampling
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn lttb_empty_input() {
+        let result = lttb_downsample_impl(&[], 5);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn lttb_data_smaller_than_threshold() {
+        let data = vec![dp(1.0, 10.0), dp(2.0, 20.0)];
+        let result = lttb_downsample_impl(&data, 5);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].ts, 1.0);
+        assert_eq!(result[1].ts, 2.0);
+    }
+
+    #[test]
+    fn lttb_threshold_less_than_3() {
+        let data = vec![dp(1.0, 10.0), dp(2.0, 20.0), dp(3.0, 30.0)];
+        let result = lttb_downsample_impl(&data, 2);
+        assert_eq!(result.len(), 3, "threshold < 3 should return data as-is");
+    }
+
+    #[test]
+    fn lttb_normal_downsample() {
+        let data: Vec<DataPoint> = (0..100).map(|i| dp(i as f64, (i as f64).sin())).collect();
+        let result = lttb_downsample_impl(&data, 20);
+        assert_eq!(result.len(), 20);
+        // First and last points must be preserved.
+        assert_eq!(result[0].ts, 0.0);
+        assert_eq!(result[19].ts, 99.0);
+    }
+
+    #[test]
+    fn lttb_threshold_of_3() {
+        let data: Vec<DataPoint> = (0..10).map(|i| dp(i as f64, (i * i) as f64)).collect();
+        let result = lttb_downsample_impl(&data, 3);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].ts, 0.0);
+        assert_eq!(result[2].ts, 9.0);
+    }
+
+    #[test]
+    fn lttb_data_equal_to_threshold() {
+        let data: Vec<DataPoint> = (0..5).map(|i| dp(i as f64, i as f64)).collect();
+        let result = lttb_downsample_impl(&data, 5);
+        assert_eq!(result.len(), 5);
+    }
+
+    // -----------------------------------------------------------------------
+    // SMA
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn sma_normal_case() {
+        let data = sample_prices();
+        let result = calc_sma_impl(&data, 3);
+        // First value: avg of close[0..3] = (11+12+13)/3 = 12.0
+        assert_eq!(result.len(), 8);
+        assert!((result[0].value - 12.0).abs() < 1e-9);
+        assert_eq!(result[0].ts, 3.0);
+        // Second value: avg of close[1..4] = (12+13+14)/3 = 13.0
+        assert!((result[1].value - 13.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sma_period_greater_than_data() {
+        let data = sample_prices();
+        let result = calc_sma_impl(&data, 100);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn sma_period_of_1() {
+        let data = sample_prices();
+        let result = calc_sma_impl(&data, 1);
+        assert_eq!(result.len(), data.len());
+        for (i, point) in result.iter().enumerate() {
+            assert!((point.value - data[i].close).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn sma_period_equals_data_len() {
+        let data = sample_prices();
+        let n = data.len();
+        let result = calc_sma_impl(&data, n);
+        assert_eq!(result.len(), 1);
+        let expected: f64 = data.iter().map(|p| p.close).sum::<f64>() / n as f64;
+        assert!((result[0].value - expected).abs() < 1e-9);
+    }
+
+    // -----------------------------------------------------------------------
+    // EMA
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn ema_normal_case() {
+        let data = sample_prices();
+        let result = calc_ema_impl(&data, 3);
+        assert_eq!(result.len(), 8);
+        // First EMA value must equal the SMA of the first 3 closes.
+        let first_sma = (11.0 + 12.0 + 13.0) / 3.0;
+        assert!((result[0].value - first_sma).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ema_first_value_matches_sma() {
+        let data = sample_prices();
+        let period = 5;
+        let ema_result = calc_ema_impl(&data, period);
+        let sma_result = calc_sma_impl(&data, period);
+        // The first EMA value should equal the first SMA value.
+        assert!(
+            (ema_result[0].value - sma_result[0].value).abs() < 1e-9,
+            "First EMA ({}) should match first SMA ({})",
+            ema_result[0].value,
+            sma_result[0].value
+        );
+    }
+
+    #[test]
+    fn ema_period_greater_than_data() {
+        let data = sample_prices();
+        let result = calc_ema_impl(&data, 100);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn ema_multiplier_correctness() {
+        let data = sample_prices();
+        let period = 3;
+        let k = 2.0 / (period as f64 + 1.0); // 0.5
+        let result = calc_ema_impl(&data, period);
+        // Verify second EMA value manually.
+        let first_ema = (11.0 + 12.0 + 13.0) / 3.0; // 12.0
+        let second_ema = data[3].close * k + first_ema * (1.0 - k); // 14*0.5 + 12*0.5 = 13.0
+        assert!((result[1].value - second_ema).abs() < 1e-9);
+    }
+
+    // -----------------------------------------------------------------------
+    // RSI
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn rsi_normal_case() {
+        let data = sample_prices();
+        let result = calc_rsi_impl(&data, 5);
+        assert!(!result.is_empty());
+        for point in &result {
+            assert!(point.value >= 0.0 && point.value <= 100.0);
+        }
+    }
+
+    #[test]
+    fn rsi_all_gains() {
+        // Monotonically increasing closes -> RSI = 100.
+        let data: Vec<PricePoint> = (0..10)
+            .map(|i| pp(i as f64, 0.0, 0.0, 0.0, 10.0 + i as f64, 100.0))
+            .collect();
+        let result = calc_rsi_impl(&data, 5);
+        assert!(!result.is_empty());
+        for point in &result {
+            assert!(
+                (point.value - 100.0).abs() < 1e-9,
+                "Expected RSI=100, got {}",
+                point.value
+            );
+        }
+    }
+
+    #[test]
+    fn rsi_all_losses() {
+        // Monotonically decreasing closes -> RSI = 0.
+        let data: Vec<PricePoint> = (0..10)
+            .map(|i| pp(i as f64, 0.0, 0.0, 0.0, 100.0 - i as f64, 100.0))
+            .collect();
+        let result = calc_rsi_impl(&data, 5);
+        assert!(!result.is_empty());
+        for point in &result {
+            assert!(
+                point.value.abs() < 1e-9,
+                "Expected RSI=0, got {}",
+                point.value
+            );
+        }
+    }
+
+    #[test]
+    fn rsi_insufficient_data() {
+        let data = vec![pp(1.0, 0.0, 0.0, 0.0, 10.0, 100.0)];
+        let result = calc_rsi_impl(&data, 5);
+        assert!(result.is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // VWAP
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn vwap_normal_case() {
+        let data = sample_prices();
+        let result = calc_vwap_impl(&data);
+        assert_eq!(result.len(), data.len());
+
+        // First VWAP value: typical = (12+9+11)/3 = 32/3 ≈ 10.6667
+        let tp0 = (12.0 + 9.0 + 11.0) / 3.0;
+        assert!(
+            (result[0].value - tp0).abs() < 1e-4,
+            "Expected ~{}, got {}",
+            tp0,
+            result[0].value
+        );
+    }
+
+    #[test]
+    fn vwap_single_point() {
+        let data = vec![pp(1.0, 10.0, 15.0, 5.0, 12.0, 1000.0)];
+        let result = calc_vwap_impl(&data);
+        assert_eq!(result.len(), 1);
+        let expected_tp = (15.0 + 5.0 + 12.0) / 3.0; // ~10.6667
+        assert!((result[0].value - expected_tp).abs() < 1e-9);
+    }
+
+    #[test]
+    fn vwap_empty() {
+        let result = calc_vwap_impl(&[]);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn vwap_cumulative_behaviour() {
+        let data = vec![
+            pp(1.0, 0.0, 30.0, 10.0, 20.0, 100.0),
+            pp(2.0, 0.0, 60.0, 20.0, 40.0, 200.0),
+        ];
+        let result = calc_vwap_impl(&data);
+        // Point 1: tp = (30+10+20)/3 = 20, cum_tp_vol = 2000, cum_vol = 100, vwap = 20
+        assert!((result[0].value - 20.0).abs() < 1e-9);
+        // Point 2: tp = (60+20+40)/3 = 40, cum_tp_vol = 2000+8000 = 10000, cum_vol = 300
+        let expected = 10000.0 / 300.0;
+        assert!((result[1].value - expected).abs() < 1e-9);
+    }
+
+    // -----------------------------------------------------------------------
+    // filter_symbols
+    // -----------------------------------------------------------------------
+
+    fn sample_entries() -> Vec<SymbolEntry> {
+        vec![
+            SymbolEntry {
+                symbol: "AAPL".to_string(),
+                name: "Apple Inc.".to_string(),
+                rank: None,
+            },
+            SymbolEntry {
+                symbol: "MSFT".to_string(),
+                name: "Microsoft Corporation".to_string(),
+                rank: None,
+            },
+            SymbolEntry {
+                symbol: "AMZN".to_string(),
+                name: "Amazon.com Inc.".to_string(),
+                rank: None,
+            },
+            SymbolEntry {
+                symbol: "GOOG".to_string(),
+                name: "Alphabet Inc.".to_string(),
+                rank: None,
+            },
+            SymbolEntry {
+                symbol: "META".to_string(),
+                name: "Meta Platforms Inc.".to_string(),
+                rank: None,
+            },
+            SymbolEntry {
+                symbol: "TSLA".to_string(),
+                name: "Tesla Inc.".to_string(),
+                rank: None,
+            },
+            SymbolEntry {
+                symbol: "AA".to_string(),
+                name: "Alcoa Corporation".to_string(),
+                rank: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn filter_exact_match_scores_highest() {
+        let entries = sample_entries();
+        let result = filter_symbols_impl(&entries, "AAPL", 10);
+        assert!(!result.is_empty());
+        assert_eq!(result[0].symbol, "AAPL");
+    }
+
+    #[test]
+    fn filter_prefix_match() {
+        let entries = sample_entries();
+        let result = filter_symbols_impl(&entries, "AA", 10);
+        // "AA" exact match on AA (100), "AAPL" starts with "AA" (80)
+        assert!(result.len() >= 2);
+        assert_eq!(result[0].symbol, "AA", "Exact match should rank first");
+        assert_eq!(result[1].symbol, "AAPL", "Prefix match should rank second");
+    }
+
+    #[test]
+    fn filter_no_match() {
+        let entries = sample_entries();
+        let result = filter_symbols_impl(&entries, "XYZ", 10);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn filter_case_insensitivity() {
+        let entries = sample_entries();
+        let result = filter_symbols_impl(&entries, "aapl", 10);
+        assert!(!result.is_empty());
+        assert_eq!(result[0].symbol, "AAPL");
+    }
+
+    #[test]
+    fn filter_name_match() {
+        let entries = sample_entries();
+        let result = filter_symbols_impl(&entries, "Tesla", 10);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].symbol, "TSLA");
+    }
+
+    #[test]
+    fn filter_max_results_limit() {
+        let entries = sample_entries();
+        let result = filter_symbols_impl(&entries, "a", 2);
+        assert_eq!(result.len(), 2);
+    }
+
+    #[test]
+    fn filter_name_contains() {
+        let entries = sample_entries();
+        // "form" is contained in "Meta Platforms Inc." -> score 20
+        let result = filter_symbols_impl(&entries, "Platforms", 10);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].symbol, "META");
+    }
+
+    #[test]
+    fn filter_empty_query_returns_up_to_max() {
+        let entries = sample_entries();
+        let result = filter_symbols_impl(&entries, "", 3);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn filter_whitespace_query_is_empty() {
+        let entries = sample_entries();
+        let result = filter_symbols_impl(&entries, " ", 3);
+        assert_eq!(result, entries[..3].to_vec());
+        let index = SymbolIndex::from_entries(entries.clone());
+        assert_eq!(index.search("   ", 3), entries[..3].to_vec());
+    }
+
+    #[test]
+    fn filter_trims_query() {
+        let entries = sample_entries();
+        let padded = filter_symbols_impl(&entries, "  aapl  ", 10);
+        assert_eq!(padded, filter_symbols_impl(&entries, "aapl", 10));
+        assert_eq!(padded[0].symbol, "AAPL");
+        let index = SymbolIndex::from_entries(entries.clone());
+        assert_eq!(index.search("  aapl  ", 10), padded);
+    }
+
+    #[test]
+    fn filter_symbol_contains() {
+        let entries = sample_entries();
+        // "OO" is contained in "GOOG" -> symbol contains = 60
+        let result = filter_symbols_impl(&entries, "OO", 10);
+        assert!(!result.is_empty());
+        assert_eq!(result[0].symbol, "GOOG");
+    }
+
+    // -----------------------------------------------------------------------
+    // Build info
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn build_info_contains_version_and_target() {
+        assert_eq!(version(), env!("CARGO_PKG_VERSION"));
+        let info = build_info();
+        assert!(info.contains(&format!("\"version\":\"{}\"", VERSION)));
+        assert!(info.contains(&format!("\"target\":\"{}\"", TARGET)));
+    }
+
+    // -----------------------------------------------------------------------
+    // Boundary sizes (capacity arithmetic must never underflow)
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn boundary_sma_ema_period_equals_len() {
+        let data = sample_prices();
+        let n = data.len();
+        assert_eq!(calc_sma_impl(&data, n).len(), 1);
+        assert_eq!(calc_ema_impl(&data, n).len(), 1);
+        assert!(calc_sma_impl(&data, n + 1).is_empty());
+        assert!(calc_ema_impl(&data, n + 1).is_empty());
+    }
+
+    #[test]
+    fn boundary_rsi_period_equals_len() {
+        let data = sample_prices();
+        let n = data.len();
+        assert!(calc_rsi_impl(&data, n).is_empty());
+        assert_eq!(calc_rsi_impl(&data, n - 1).len(), 1);
+    }
+
+    #[test]
+    fn boundary_huge_period() {
+        let data = sample_prices();
+        assert!(calc_sma_impl(&data, usize::MAX).is_empty());
+        assert!(calc_ema_impl(&data, usize::MAX).is_empty());
+        assert!(calc_rsi_impl(&data, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn boundary_single_bar_inputs() {
+        let data = vec![pp(1.0, 10.0, 11.0, 9.0, 10.0, 100.0)];
+        assert_eq!(calc_sma_impl(&data, 1).len(), 1);
+        assert_eq!(calc_ema_impl(&data, 1).len(), 1);
+        assert!(calc_rsi_impl(&data, 1).is_empty());
+        assert_eq!(calc_vwap_impl(&data).len(), 1);
+    }
+
+    #[test]
+    fn boundary_lttb_len_one_above_threshold() {
+        let data: Vec<DataPoint> = (0..6).map(|i| dp(i as f64, i as f64)).collect();
+        let result = lttb_downsample_impl(&data, 5);
+        assert_eq!(result.len(), 5);
+        assert_eq!(result[0].ts, 0.0);
+        assert_eq!(result[4].ts, 5.0);
+    }
+
+    // -----------------------------------------------------------------------
+    // RSI smoothing methods
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn rsi_smoothed_wilder_matches_default() {
+        let data = sample_prices();
+        let default = calc_rsi_impl(&data, 3);
+        let wilder = calc_rsi_smoothed_impl(&data, 3, SmoothingMethod::Wilder);
+        assert_eq!(default, wilder);
+    }
+
+    #[test]
+    fn rsi_smoothed_methods_diverge() {
+        let data = sample_prices();
+        let wilder = calc_rsi_smoothed_impl(&data, 3, SmoothingMethod::Wilder);
+        let ema = calc_rsi_smoothed_impl(&data, 3, SmoothingMethod::Ema);
+        let sma = calc_rsi_smoothed_impl(&data, 3, SmoothingMethod::Sma);
+        assert_eq!(wilder.len(), ema.len());
+        assert_eq!(wilder.len(), sma.len());
+        // Same seed, so the first value agrees...
+        assert!((wilder[0].value - ema[0].value).abs() < 1e-9);
+        assert!((wilder[0].value - sma[0].value).abs() < 1e-9);
+        // ...but the smoothing differs once the series turns.
+        let last = wilder.len() - 1;
+        assert!((wilder[last].value - ema[last].value).abs() > 1e-6);
+        assert!((wilder[last].value - sma[last].value).abs() > 1e-6);
+        assert!((ema[last].value - sma[last].value).abs() > 1e-6);
+    }
+
+    #[test]
+    fn rsi_smoothed_sma_is_rolling_window() {
+        let data = sample_prices();
+        let result = calc_rsi_smoothed_impl(&data, 3, SmoothingMethod::Sma);
+        // Last 3 changes: 13->12, 12->11, 11->10 are all losses.
+        assert!(result.last().unwrap().value.abs() < 1e-9);
+    }
+
+    #[test]
+    fn smoothing_method_from_name() {
+        assert_eq!(
+            SmoothingMethod::from_name("EMA"),
+            Some(SmoothingMethod::Ema)
+        );
+        assert_eq!(
+            SmoothingMethod::from_name("wilder"),
+            Some(SmoothingMethod::Wilder)
+        );
+        assert_eq!(
+            SmoothingMethod::from_name("sma"),
+            Some(SmoothingMethod::Sma)
+        );
+        assert_eq!(SmoothingMethod::from_name("hull"), None);
+    }
+
+    // -----------------------------------------------------------------------
+    // Columnar OHLCV parsing
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn columnar_to_prices_transposes() {
+        let cols = OhlcvColumns {
+            ts: vec![1.0, 2.0],
+            open: vec![10.0, 11.0],
+            high: vec![12.0, 13.0],
+            low: vec![9.0, 10.0],
+            close: vec![11.0, 12.0],
+            volume: vec![100.0, 150.0],
+        };
+        let result = columnar_to_prices(&cols).unwrap();
+        assert_eq!(result, sample_prices()[..2].to_vec());
+    }
+
+    #[test]
+    fn columnar_to_prices_empty() {
+        let result = columnar_to_prices(&OhlcvColumns::default()).unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn columnar_to_prices_length_mismatch() {
+        let cols = OhlcvColumns {
+            ts: vec![1.0, 2.0],
+            open: vec![10.0, 11.0],
+            high: vec![12.0, 13.0],
+            low: vec![9.0, 10.0],
+            close: vec![11.0],
+            volume: vec![100.0, 150.0],
+        };
+        let err = columnar_to_prices(&cols).unwrap_err();
+        assert!(err.contains("close"), "unexpected error: {}", err);
+    }
+
+    // -----------------------------------------------------------------------
+    // Anchored VWAP
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn vwap_from_starts_at_anchor() {
+        let data = sample_prices();
+        let result = calc_vwap_from_impl(&data, 4.0);
+        assert_eq!(result.len(), 7);
+        assert_eq!(result[0].ts, 4.0);
+        // First anchored value is just the anchor bar's typical price.
+        let tp = (15.0 + 12.0 + 14.0) / 3.0;
+        assert!((result[0].value - tp).abs() < 1e-9);
+        assert_eq!(result, calc_vwap_impl(&data[3..]));
+    }
+
+    #[test]
+    fn vwap_from_between_bars_uses_next_bar() {
+        let data = sample_prices();
+        let result = calc_vwap_from_impl(&data, 3.5);
+        assert_eq!(result[0].ts, 4.0);
+    }
+
+    #[test]
+    fn vwap_from_anchor_past_end() {
+        let data = sample_prices();
+        assert!(calc_vwap_from_impl(&data, 11.0).is_empty());
+        assert!(calc_vwap_from_impl(&[], 0.0).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Timeframe aggregation
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn aggregate_groups_bars() {
+        let data = sample_prices();
+        let result = aggregate_timeframe_impl(&data, 5);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], pp(1.0, 10.0, 16.0, 9.0, 15.0, 750.0));
+        assert_eq!(result[1], pp(6.0, 15.0, 17.0, 9.0, 10.0, 640.0));
+    }
+
+    #[test]
+    fn aggregate_trailing_partial_group() {
+        let data = sample_prices();
+        let result = aggregate_timeframe_impl(&data, 4);
+        assert_eq!(result.len(), 3);
+        // Last group holds bars 9 and 10 only.
+        assert_eq!(result[2], pp(9.0, 12.0, 13.0, 9.0, 10.0, 210.0));
+    }
+
+    #[test]
+    fn aggregate_factor_one_is_identity() {
+        let data = sample_prices();
+        assert_eq!(aggregate_timeframe_impl(&data, 1), data);
+        assert_eq!(aggregate_timeframe_impl(&data, 0), data);
+    }
+
+    // -----------------------------------------------------------------------
+    // Swings and level clustering
+    // -----------------------------------------------------------------------
+
+    fn swing(price: f64, kind: SwingKind) -> Swing {
+        Swing {
+            ts: 0.0,
+            index: 0,
+            price,
+            kind,
+        }
+    }
+
+    #[test]
+    fn swings_find_peak_and_trough() {
+        let data = sample_prices();
+        let result = detect_swings_impl(&data, 2, 2);
+        // High of 17 at ts=6 is the only pivot with two bars on each side.
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].kind, SwingKind::High);
+        assert_eq!(result[0].ts, 6.0);
+        assert_eq!(result[0].price, 17.0);
+    }
+
+    #[test]
+    fn swings_insufficient_data() {
+        let data = sample_prices();
+        assert!(detect_swings_impl(&data, 5, 5).is_empty());
+        assert!(detect_swings_impl(&[], 1, 1).is_empty());
+    }
+
+    #[test]
+    fn cluster_levels_groups_nearby_prices() {
+        let swings = vec![
+            swing(100.0, SwingKind::High),
+            swing(100.4, SwingKind::High),
+            swing(99.8, SwingKind::Low),
+            swing(110.0, SwingKind::High),
+        ];
+        let result = cluster_levels_impl(&swings, 1.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].touches, 3);
+        assert!((result[0].price - (99.8 + 100.0 + 100.4) / 3.0).abs() < 1e-9);
+        assert_eq!(result[1].touches, 1);
+        assert_eq!(result[1].price, 110.0);
+    }
+
+    #[test]
+    fn cluster_levels_zero_tolerance_keeps_distinct() {
+        let swings = vec![swing(10.0, SwingKind::Low), swing(10.5, SwingKind::Low)];
+        let result = cluster_levels_impl(&swings, 0.0);
+        assert_eq!(result.len(), 2);
+        assert!(cluster_levels_impl(&[], 1.0).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Percent rank
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn percent_rank_max_value_is_100() {
+        let data = sample_prices();
+        let result = calc_percent_rank_impl(&data, 4, PriceSource::Close);
+        assert_eq!(result.len(), 6);
+        // Bar 5 closes at 15, above the prior four closes 11..=14.
+        assert_eq!(result[0].ts, 5.0);
+        assert!((result[0].value - 100.0).abs() < 1e-9);
+        // Bar 10 closes at 10, below everything in its window.
+        assert!(result[5].value.abs() < 1e-9);
+    }
+
+    #[test]
+    fn percent_rank_ties_are_not_counted() {
+        let data: Vec<PricePoint> = (0..4)
+            .map(|i| pp(i as f64, 0.0, 0.0, 0.0, 5.0, 1.0))
+            .collect();
+        let result = calc_percent_rank_impl(&data, 3, PriceSource::Close);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].value, 0.0);
+    }
+
+    #[test]
+    fn percent_rank_over_volume() {
+        let data = sample_prices();
+        let result = calc_percent_rank_impl(&data, 2, PriceSource::Volume);
+        // Bar 3 volume 200 vs prior 100, 150.
+        assert!((result[0].value - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn percent_rank_insufficient_data() {
+        let data = sample_prices();
+        assert!(calc_percent_rank_impl(&data, 10, PriceSource::Close).is_empty());
+        assert!(calc_percent_rank_impl(&data, 0, PriceSource::Close).is_empty());
+    }
+
+    #[test]
+    fn price_source_values() {
+        let p = pp(1.0, 10.0, 14.0, 8.0, 12.0, 500.0);
+        assert_eq!(PriceSource::Median.value(&p), 11.0);
+        assert_eq!(PriceSource::Typical.value(&p), 34.0 / 3.0);
+        assert_eq!(PriceSource::WeightedClose.value(&p), 11.5);
+        assert_eq!(PriceSource::Ohlc4.value(&p), 11.0);
+        assert_eq!(PriceSource::from_name("hlc3"), Some(PriceSource::Typical));
+        assert_eq!(PriceSource::from_name("bogus"), None);
+    }
+
+    // -----------------------------------------------------------------------
+    // Rolling highest / lowest
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn rolling_highest_and_lowest() {
+        let data = sample_prices();
+        let highs = rolling_highest_impl(&data, 3, PriceSource::High);
+        let lows = rolling_lowest_impl(&data, 3, PriceSource::Low);
+        assert_eq!(highs.len(), 8);
+        assert_eq!(highs[0].ts, 3.0);
+        assert_eq!(highs[0].value, 14.0);
+        // Window ts 6..=8 peaks at the ts=6 high of 17.
+        assert_eq!(highs[5].value, 17.0);
+        assert_eq!(lows[0].value, 9.0);
+        assert_eq!(lows[7].value, 9.0);
+    }
+
+    #[test]
+    fn bars_since_extremes() {
+        let data = sample_prices();
+        let since_high = bars_since_highest_impl(&data, 3, PriceSource::High);
+        let since_low = bars_since_lowest_impl(&data, 3, PriceSource::Low);
+        // Rising highs: the current bar is always the high until ts=6.
+        assert_eq!(since_high[0].value, 0.0);
+        // Window ending ts=8 has its high two bars back at ts=6.
+        assert_eq!(since_high[5].value, 2.0);
+        // Window ending ts=3 has its low two bars back at ts=1.
+        assert_eq!(since_low[0].value, 2.0);
+        assert_eq!(since_low[7].value, 0.0);
+    }
+
+    #[test]
+    fn rolling_extreme_ties_prefer_most_recent() {
+        let data: Vec<PricePoint> = (0..3)
+            .map(|i| pp(i as f64, 0.0, 5.0, 1.0, 3.0, 1.0))
+            .collect();
+        let since_high = bars_since_highest_impl(&data, 3, PriceSource::High);
+        assert_eq!(since_high[0].value, 0.0);
+    }
+
+    #[test]
+    fn rolling_extreme_degenerate_period() {
+        let data = sample_prices();
+        assert!(rolling_highest_impl(&data, 0, PriceSource::Close).is_empty());
+        assert!(rolling_lowest_impl(&data, 11, PriceSource::Close).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // LTTB with pinned event points
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn lttb_keep_includes_event_points() {
+        let data: Vec<DataPoint> = (0..100).map(|i| dp(i as f64, (i as f64).sin())).collect();
+        let plain = lttb_downsample_impl(&data, 20);
+        // Pick a timestamp plain LTTB dropped.
+        let dropped = (1..99)
+            .map(|i| i as f64)
+            .find(|ts| !plain.iter().any(|p| p.ts == *ts))
+            .unwrap();
+
+        let result = lttb_downsample_keep_impl(&data, 20, &[dropped]);
+        assert_eq!(result.len(), 20);
+        assert!(result.iter().any(|p| p.ts == dropped));
+        assert_eq!(result[0].ts, 0.0);
+        assert_eq!(result[19].ts, 99.0);
+        assert!(result.windows(2).all(|w| w[0].ts < w[1].ts));
+    }
+
+    #[test]
+    fn lttb_keep_same_bucket_collision_inserts() {
+        let data: Vec<DataPoint> = (0..100).map(|i| dp(i as f64, (i as f64).sin())).collect();
+        // Buckets are ~5.4 points wide; indices 12 and 13 both fall in
+        // the bucket covering 11..17.
+        let result = lttb_downsample_keep_impl(&data, 20, &[12.0, 13.0]);
+        assert_eq!(result.len(), 21);
+        assert!(result.iter().any(|p| p.ts == 12.0));
+        assert!(result.iter().any(|p| p.ts == 13.0));
+        assert!(result.windows(2).all(|w| w[0].ts < w[1].ts));
+    }
+
+    #[test]
+    fn lttb_keep_without_events_matches_plain() {
+        let data: Vec<DataPoint> = (0..50).map(|i| dp(i as f64, (i * i % 7) as f64)).collect();
+        assert_eq!(
+            lttb_downsample_keep_impl(&data, 10, &[]),
+            lttb_downsample_impl(&data, 10)
+        );
+        // Unknown timestamps are ignored.
+        assert_eq!(
+            lttb_downsample_keep_impl(&data, 10, &[123.5]),
+            lttb_downsample_impl(&data, 10)
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Batch indicators and timing
+    // -----------------------------------------------------------------------
+
+    fn spec(kind: &str, period: usize) -> IndicatorSpec {
+        IndicatorSpec {
+            kind: kind.to_string(),
+            period,
+            key: None,
+        }
+    }
+
+    #[test]
+    fn compute_indicators_matches_individual_calls() {
+        let data = sample_prices();
+        let specs = vec![spec("sma", 3), spec("rsi", 5), spec("vwap", 0)];
+        let result = compute_indicators_impl(&data, &specs);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result["sma_3"], calc_sma_impl(&data, 3));
+        assert_eq!(result["rsi_5"], calc_rsi_impl(&data, 5));
+        assert_eq!(result["vwap"], calc_vwap_impl(&data));
+    }
+
+    #[test]
+    fn compute_indicators_custom_key_and_unknown_kind() {
+        let data = sample_prices();
+        let mut fast = spec("ema", 3);
+        fast.key = Some("fast".to_string());
+        let result = compute_indicators_impl(&data, &[fast, spec("hull", 9)]);
+        assert_eq!(result["fast"], calc_ema_impl(&data, 3));
+        assert!(result["hull_9"].is_empty());
+    }
+
+    #[test]
+    fn compute_indicators_timed_reports_microseconds() {
+        let data = sample_prices();
+        let specs = vec![spec("sma", 3), spec("ema", 3)];
+        // Fake clock advancing 0.5 ms per read.
+        let mut t = 0.0;
+        let result = compute_indicators_timed_impl(&data, &specs, || {
+            t += 0.5;
+            t
+        });
+        assert_eq!(result.results, compute_indicators_impl(&data, &specs));
+        assert_eq!(result.timings["sma_3"], 500.0);
+        assert_eq!(result.timings["ema_3"], 500.0);
+    }
+
+    // -----------------------------------------------------------------------
+    // SymbolIndex
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn symbol_index_queries_cached_entries() {
+        let index = SymbolIndex::from_entries(sample_entries());
+        assert_eq!(index.len(), 7);
+
+        let first = index.search("AA", 10);
+        assert_eq!(first, filter_symbols_impl(&sample_entries(), "AA", 10));
+        assert_eq!(first[0].symbol, "AA");
+
+        let second = index.search("tesla", 10);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].symbol, "TSLA");
+    }
+
+    #[test]
+    fn symbol_index_empty() {
+        let index = SymbolIndex::from_entries(Vec::new());
+        assert!(index.is_empty());
+        assert!(index.search("a", 10).is_empty());
+    }
+
+    #[test]
+    fn symbol_index_prefix_fast_path_matches_linear_scan() {
+        let mut entries = sample_entries();
+        for i in 0..50 {
+            entries.push(SymbolEntry {
+                symbol: format!("A{:02}", i),
+                name: format!("Alpha Holdings {}", i),
+                rank: None,
+            });
+        }
+        let index = SymbolIndex::from_entries(entries.clone());
+        for query in ["a", "A0", "aa", "aapl", "m", "oo", "inc", "", "zz"] {
+            for max in [0, 1, 3, 10, 100] {
+                assert_eq!(
+                    index.search(query, max),
+                    filter_symbols_impl(&entries, query, max),
+                    "query={:?} max={}",
+                    query,
+                    max
+                );
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Value-based EMA helper
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn ema_over_values_matches_calc_ema() {
+        let data = sample_prices();
+        let closes: Vec<f64> = data.iter().map(|p| p.close).collect();
+        let values = ema_over_values(&closes, 4);
+        let points = calc_ema_impl(&data, 4);
+        assert_eq!(values.len(), points.len());
+        for (v, p) in values.iter().zip(&points) {
+            assert!((v - p.value).abs() < 1e-12);
+        }
+        assert!(ema_over_values(&closes, 0).is_empty());
+        assert!(ema_over_values(&closes, 11).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Klinger Volume Oscillator
+    // -----------------------------------------------------------------------
+
+    fn rising_prices(n: usize) -> Vec<PricePoint> {
+        (0..n)
+            .map(|i| {
+                let c = 100.0 + i as f64;
+                pp(i as f64, c - 0.5, c + 1.0, c - 1.0, c, 1000.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn klinger_monotonic_series_is_positive() {
+        let data = rising_prices(40);
+        let result = calc_klinger_impl(&data, 5, 10, 3);
+        // Volume force is defined from bar 1, so KVO starts at data[10].
+        assert_eq!(result.kvo.len(), 30);
+        assert_eq!(result.kvo[0].ts, 10.0);
+        assert_eq!(result.signal.len(), 28);
+        assert_eq!(result.signal[0].ts, result.kvo[2].ts);
+        for p in &result.kvo {
+            assert!(p.value > 0.0, "expected positive KVO, got {}", p.value);
+        }
+    }
+
+    #[test]
+    fn klinger_matches_hand_computed_table() {
+        let bars = [
+            (10.0, 8.0, 9.0, 100.0),
+            (11.5, 9.0, 10.5, 150.0),
+            (12.0, 10.5, 11.5, 120.0),
+            (11.5, 9.0, 10.0, 200.0),
+            (10.0, 8.0, 8.5, 180.0),
+            (11.0, 9.5, 10.8, 160.0),
+            (12.5, 10.0, 12.0, 140.0),
+            (12.0, 10.8, 10.5, 170.0),
+            (12.4, 10.6, 12.2, 130.0),
+        ];
+        let data: Vec<PricePoint> = bars
+            .iter()
+            .enumerate()
+            .map(|(i, &(h, l, c, v))| pp(i as f64, c, h, l, c, v))
+            .collect();
+        // bar  trend  dm   cm                 vf
+        //  1    +1   2.5  4.5 (2.0 + 2.5)     150 * |5/4.5 - 1| * 100 =  1666.67
+        //  2    +1   1.5  6.0 (carried)       120 * |3/6 - 1| * 100   =  6000
+        //  3    -1   2.5  4.0 (1.5 + 2.5)    -200 * |5/4 - 1| * 100   = -5000
+        //  4    -1   2.0  6.0 (carried)      -180 * |4/6 - 1| * 100   = -6000
+        //  5    +1   1.5  3.5 (2.0 + 1.5)     160 * |3/3.5 - 1| * 100 =  2285.71
+        //  6    +1   2.5  6.0 (carried)       140 * |5/6 - 1| * 100   =  2333.33
+        //  7    -1   1.2  3.7 (2.5 + 1.2)    -170 * |2.4/3.7 - 1| * 100 = -5972.97
+        //  8    +1   1.8  3.0 (1.2 + 1.8)     130 * |3.6/3 - 1| * 100 =  2600
+        // KVO = EMA2(vf) - EMA3(vf), both SMA-seeded; signal = EMA2(KVO).
+        let result = calc_klinger_impl(&data, 2, 3, 2);
+        let kvo = [
+            (3.0, -2944.4444444444),
+            (4.0, -2129.6296296296),
+            (5.0, 97.0017636684),
+            (6.0, 443.7095825985),
+            (7.0, -1030.7933594971),
+            (8.0, 495.8827654815),
+        ];
+        let signal = [
+            (4.0, -2537.037037037),
+            (5.0, -781.0111699001),
+            (6.0, 35.4693317656),
+            (7.0, -675.3724624095),
+            (8.0, 105.4643561845),
+        ];
+        for (actual, expected) in [(&result.kvo, &kvo[..]), (&result.signal, &signal[..])] {
+            assert_eq!(actual.len(), expected.len());
+            for (p, &(ts, value)) in actual.iter().zip(expected) {
+                assert_eq!(p.ts, ts);
+                assert!((p.value - value).abs() < 1e-6, "{} vs {}", p.value, value);
+            }
+        }
+    }
+
+    #[test]
+    fn klinger_insufficient_data() {
+        let data = rising_prices(10);
+        let result = calc_klinger_impl(&data, 5, 10, 3);
+        assert!(result.kvo.is_empty());
+        assert!(result.signal.is_empty());
+        assert!(calc_klinger_impl(&data, 0, 5, 3).kvo.is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Fisher Transform
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn fisher_alignment_and_trigger_lag() {
+        let data = sample_prices();
+        let result = calc_fisher_impl(&data, 3);
+        assert_eq!(result.fisher.len(), 8);
+        assert_eq!(result.fisher[0].ts, 3.0);
+        assert_eq!(result.trigger.len(), 7);
+        for (i, t) in result.trigger.iter().enumerate() {
+            assert_eq!(t.ts, result.fisher[i + 1].ts);
+            assert_eq!(t.value, result.fisher[i].value);
+        }
+    }
+
+    #[test]
+    fn fisher_tracks_direction_and_stays_finite() {
+        let data = sample_prices();
+        let result = calc_fisher_impl(&data, 3);
+        // Prices rise through ts=5, then fall.
+        assert!(result.fisher[2].value > 0.0);
+        assert!(result.fisher[7].value < 0.0);
+        // A long one-way run saturates the clamp without producing inf.
+        let rising = rising_prices(200);
+        for p in calc_fisher_impl(&rising, 5).fisher {
+            assert!(p.value.is_finite());
+        }
+    }
+
+    #[test]
+    fn fisher_insufficient_data() {
+        let data = sample_prices();
+        assert!(calc_fisher_impl(&data, 11).fisher.is_empty());
+        assert!(calc_fisher_impl(&data, 0).trigger.is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Balance of Power and candle geometry
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn candle_geometry_helpers() {
+        let up = pp(1.0, 10.0, 15.0, 8.0, 12.0, 0.0);
+        assert_eq!(body_size(&up), 2.0);
+        assert_eq!(upper_wick(&up), 3.0);
+        assert_eq!(lower_wick(&up), 2.0);
+        let down = pp(1.0, 12.0, 15.0, 8.0, 10.0, 0.0);
+        assert_eq!(body_size(&down), 2.0);
+        assert_eq!(upper_wick(&down), 3.0);
+        assert_eq!(lower_wick(&down), 2.0);
+    }
+
+    #[test]
+    fn bop_raw_per_bar() {
+        let data = vec![
+            pp(1.0, 10.0, 14.0, 10.0, 14.0, 0.0),
+            pp(2.0, 14.0, 14.0, 10.0, 12.0, 0.0),
+            pp(3.0, 5.0, 5.0, 5.0, 5.0, 0.0),
+        ];
+        let result = calc_bop_impl(&data, 0);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].value, 1.0);
+        assert_eq!(result[1].value, -0.5);
+        assert_eq!(result[2].value, 0.0, "zero range must not divide");
+    }
+
+    #[test]
+    fn bop_smoothed() {
+        let data = vec![
+            pp(1.0, 10.0, 14.0, 10.0, 14.0, 0.0),
+            pp(2.0, 14.0, 14.0, 10.0, 12.0, 0.0),
+            pp(3.0, 5.0, 5.0, 5.0, 5.0, 0.0),
+        ];
+        let result = calc_bop_impl(&data, 2);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].ts, 2.0);
+        assert!((result[0].value - 0.25).abs() < 1e-12);
+        assert!((result[1].value + 0.25).abs() < 1e-12);
+        assert!(calc_bop_impl(&data, 4).is_empty());
+    }
+
+    #[test]
+    fn sma_over_values_matches_calc_sma() {
+        let data = sample_prices();
+        let closes: Vec<f64> = data.iter().map(|p| p.close).collect();
+        let values = sma_over_values(&closes, 3);
+        let points = calc_sma_impl(&data, 3);
+        assert_eq!(values, points.iter().map(|p| p.value).collect::<Vec<_>>());
+    }
+
+    // -----------------------------------------------------------------------
+    // Candlestick patterns
+    // -----------------------------------------------------------------------
+
+    fn patterns_at(hits: &[PatternHit], ts: f64) -> Vec<PatternKind> {
+        hits.iter()
+            .filter(|h| h.ts == ts)
+            .map(|h| h.pattern)
+            .collect()
+    }
+
+    #[test]
+    fn patterns_single_bar() {
+        let data = vec![
+            // Doji: open == close mid-range.
+            pp(1.0, 10.0, 12.0, 8.0, 10.0, 0.0),
+            // Hammer: small body at the top, long lower wick.
+            pp(2.0, 10.0, 10.6, 7.0, 10.5, 0.0),
+            // Shooting star: small body at the bottom, long upper wick.
+            pp(3.0, 10.5, 14.0, 10.0, 10.0, 0.0),
+        ];
+        let hits = detect_patterns_impl(&data);
+        assert_eq!(patterns_at(&hits, 1.0), vec![PatternKind::Doji]);
+        assert_eq!(patterns_at(&hits, 2.0), vec![PatternKind::Hammer]);
+        assert_eq!(patterns_at(&hits, 3.0), vec![PatternKind::ShootingStar]);
+    }
+
+    #[test]
+    fn patterns_engulfing() {
+        let data = vec![
+            pp(1.0, 11.0, 11.5, 9.5, 10.0, 0.0),
+            pp(2.0, 9.8, 12.5, 9.5, 12.0, 0.0),
+            pp(3.0, 12.5, 13.0, 9.0, 9.5, 0.0),
+        ];
+        let hits = detect_patterns_impl(&data);
+        assert!(patterns_at(&hits, 2.0).contains(&PatternKind::BullishEngulfing));
+        assert!(patterns_at(&hits, 3.0).contains(&PatternKind::BearishEngulfing));
+        assert!(hits.windows(2).all(|w| w[0].ts <= w[1].ts));
+    }
+
+    #[test]
+    fn patterns_thresholds_are_tunable() {
+        let data = vec![pp(1.0, 10.0, 12.0, 8.0, 10.5, 0.0)];
+        // Body / range = 0.125: not a doji by default...
+        assert!(patterns_at(&detect_patterns_impl(&data), 1.0).is_empty());
+        // ...but is with a looser ratio.
+        let loose = PatternThresholds {
+            doji_body_ratio: 0.2,
+            ..PatternThresholds::default()
+        };
+        let hits = detect_patterns_with_impl(&data, &loose);
+        assert_eq!(patterns_at(&hits, 1.0), vec![PatternKind::Doji]);
+    }
+
+    #[test]
+    fn patterns_skip_zero_range() {
+        let data = vec![pp(1.0, 5.0, 5.0, 5.0, 5.0, 0.0)];
+        assert!(detect_patterns_impl(&data).is_empty());
+        assert!(detect_patterns_impl(&[]).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Pine-style utilities
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn nz_replaces_nan_only() {
+        assert_eq!(nz(f64::NAN, 0.0), 0.0);
+        assert_eq!(nz(3.5, 0.0), 3.5);
+        assert_eq!(nz(f64::INFINITY, 0.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn change_n_bars_ago() {
+        let result = change_impl(&[1.0, 4.0, 9.0, 16.0], 2);
+        assert_eq!(result.len(), 4);
+        assert!(result[0].is_nan() && result[1].is_nan());
+        assert_eq!(result[2], 8.0);
+        assert_eq!(result[3], 12.0);
+        assert_eq!(change_impl(&[1.0, 2.0], 0), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn valuewhen_occurrences() {
+        let conditions = [false, true, false, true, false];
+        let values = [10.0, 20.0, 30.0, 40.0, 50.0];
+
+        let latest = valuewhen_impl(&conditions, &values, 0);
+        assert!(latest[0].is_nan());
+        assert_eq!(&latest[1..], &[20.0, 20.0, 40.0, 40.0]);
+
+        let previous = valuewhen_impl(&conditions, &values, 1);
+        assert!(previous[..3].iter().all(|v| v.is_nan()));
+        assert_eq!(&previous[3..], &[20.0, 20.0]);
+    }
+
+    // -----------------------------------------------------------------------
+    // filter_symbols with total count
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn filter_counted_total_exceeds_page() {
+        let entries = sample_entries();
+        let (results, total) = filter_symbols_counted_impl(&entries, "a", 2);
+        assert_eq!(results.len(), 2);
+        assert!(total > results.len());
+        assert_eq!(results, filter_symbols_impl(&entries, "a", 2));
+    }
+
+    #[test]
+    fn filter_counted_without_truncation() {
+        let entries = sample_entries();
+        let (results, total) = filter_symbols_counted_impl(&entries, "Tesla", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(total, 1);
+        let (_, none) = filter_symbols_counted_impl(&entries, "XYZ", 10);
+        assert_eq!(none, 0);
+    }
+
+    #[test]
+    fn filter_counted_empty_query_counts_all() {
+        let entries = sample_entries();
+        let (results, total) = filter_symbols_counted_impl(&entries, "", 3);
+        assert_eq!(results.len(), 3);
+        assert_eq!(total, entries.len());
+    }
+
+    // -----------------------------------------------------------------------
+    // Ultimate Oscillator
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn ultimate_alignment_and_range() {
+        let data = sample_prices();
+        let result = calc_ultimate_impl(&data, 2, 3, 4);
+        assert_eq!(result.len(), 6);
+        assert_eq!(result[0].ts, 5.0);
+        for p in &result {
+            assert!(p.value >= 0.0 && p.value <= 100.0);
+        }
+    }
+
+    #[test]
+    fn ultimate_closing_at_highs_is_100() {
+        // Each bar closes at its high and gaps above the prior close.
+        let data: Vec<PricePoint> = (0..6)
+            .map(|i| {
+                let base = 10.0 + i as f64;
+                pp(i as f64, base, base + 1.0, base, base + 1.0, 100.0)
+            })
+            .collect();
+        let result = calc_ultimate_impl(&data, 1, 2, 3);
+        for p in &result {
+            assert!((p.value - 100.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn ultimate_zero_true_range_and_insufficient_data() {
+        let flat: Vec<PricePoint> = (0..5)
+            .map(|i| pp(i as f64, 5.0, 5.0, 5.0, 5.0, 1.0))
+            .collect();
+        let result = calc_ultimate_impl(&flat, 1, 2, 3);
+        assert!(result.iter().all(|p| p.value == 0.0));
+        assert!(calc_ultimate_impl(&sample_prices(), 7, 14, 28).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Mass Index
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn mass_index_constant_range_equals_sum_period() {
+        // A constant high-low range makes both EMAs equal, so every ratio is 1.
+        let data = rising_prices(20);
+        let result = calc_mass_index_impl(&data, 3, 5);
+        assert_eq!(result.len(), 20 - (2 * 2 + 5 - 1));
+        assert_eq!(result[0].ts, 8.0);
+        for p in &result {
+            assert!((p.value - 5.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn mass_index_expanding_range_rises_above_baseline() {
+        let data: Vec<PricePoint> = (0..30)
+            .map(|i| {
+                let half = 1.0 + (i as f64) * 0.5;
+                pp(i as f64, 100.0, 100.0 + half, 100.0 - half, 100.0, 1.0)
+            })
+            .collect();
+        let result = calc_mass_index_impl(&data, 3, 5);
+        assert!(result.iter().all(|p| p.value > 5.0));
+    }
+
+    #[test]
+    fn mass_index_insufficient_data() {
+        let data = sample_prices();
+        assert!(calc_mass_index_impl(&data, 9, 25).is_empty());
+        assert!(calc_mass_index_impl(&data, 0, 2).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Negative / Positive Volume Index
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn nvi_pvi_flat_on_constant_volume() {
+        let data = rising_prices(10);
+        let nvi = calc_nvi_impl(&data);
+        let pvi = calc_pvi_impl(&data);
+        assert_eq!(nvi.len(), 10);
+        assert_eq!(pvi.len(), 10);
+        assert!(nvi.iter().all(|p| p.value == 1000.0));
+        assert!(pvi.iter().all(|p| p.value == 1000.0));
+    }
+
+    #[test]
+    fn nvi_pvi_react_to_volume_direction() {
+        let data = vec![
+            pp(1.0, 0.0, 0.0, 0.0, 100.0, 500.0),
+            pp(2.0, 0.0, 0.0, 0.0, 110.0, 400.0), // volume down, +10%
+            pp(3.0, 0.0, 0.0, 0.0, 99.0, 600.0),  // volume up, -10%
+        ];
+        let nvi = calc_nvi_impl(&data);
+        let pvi = calc_pvi_impl(&data);
+        assert!((nvi[1].value - 1100.0).abs() < 1e-9);
+        assert!((nvi[2].value - 1100.0).abs() < 1e-9);
+        assert!((pvi[1].value - 1000.0).abs() < 1e-9);
+        assert!((pvi[2].value - 900.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn nvi_zero_prior_close_is_ignored() {
+        let data = vec![
+            pp(1.0, 0.0, 0.0, 0.0, 0.0, 500.0),
+            pp(2.0, 0.0, 0.0, 0.0, 10.0, 400.0),
+        ];
+        assert_eq!(calc_nvi_impl(&data)[1].value, 1000.0);
+        assert!(calc_nvi_impl(&[]).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Accumulation / Distribution and Chaikin Oscillator
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn adl_accumulates_money_flow() {
+        let data = vec![
+            pp(1.0, 0.0, 12.0, 8.0, 12.0, 100.0), // close at high: +100
+            pp(2.0, 0.0, 12.0, 8.0, 8.0, 50.0),   // close at low: -50
+            pp(3.0, 0.0, 10.0, 10.0, 10.0, 70.0), // zero range: unchanged
+        ];
+        let result = calc_adl_impl(&data);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].value, 100.0);
+        assert_eq!(result[1].value, 50.0);
+        assert_eq!(result[2].value, 50.0);
+    }
+
+    #[test]
+    fn chaikin_matches_manual_ema_difference() {
+        let data = sample_prices();
+        let result = calc_chaikin_osc_impl(&data, 3, 5);
+        assert_eq!(result.len(), 6);
+        assert_eq!(result[0].ts, 5.0);
+
+        let adl: Vec<f64> = calc_adl_impl(&data).iter().map(|p| p.value).collect();
+        let fast = ema_over_values(&adl, 3);
+        let slow = ema_over_values(&adl, 5);
+        assert!((result[0].value - (fast[2] - slow[0])).abs() < 1e-9);
+    }
+
+    #[test]
+    fn chaikin_degenerate_periods() {
+        let data = sample_prices();
+        assert!(calc_chaikin_osc_impl(&data, 10, 3).is_empty());
+        assert!(calc_chaikin_osc_impl(&data, 3, 3).is_empty());
+        assert!(calc_chaikin_osc_impl(&data, 3, 11).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Elder Ray
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn elder_ray_against_ema() {
+        let data = sample_prices();
+        let result = calc_elder_ray_impl(&data, 3);
+        let ema = calc_ema_impl(&data, 3);
+        assert_eq!(result.bull_power.len(), ema.len());
+        assert_eq!(result.bear_power.len(), ema.len());
+        // First EMA is 12.0 at ts=3 (high 14, low 11).
+        assert_eq!(result.bull_power[0].ts, 3.0);
+        assert!((result.bull_power[0].value - 2.0).abs() < 1e-9);
+        assert!((result.bear_power[0].value + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn elder_ray_insufficient_data() {
+        let data = sample_prices();
+        let result = calc_elder_ray_impl(&data, 11);
+        assert!(result.bull_power.is_empty());
+        assert!(result.bear_power.is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Timestamp units
+    // -----------------------------------------------------------------------
+
+    fn bars_at(ts: &[f64]) -> Vec<PricePoint> {
+        ts.iter().map(|t| pp(*t, 1.0, 1.0, 1.0, 1.0, 1.0)).collect()
+    }
+
+    #[test]
+    fn detect_ts_unit_by_magnitude() {
+        assert_eq!(
+            detect_ts_unit(&bars_at(&[1.7e9, 1.7e9 + 60.0])),
+            TsUnit::Seconds
+        );
+        assert_eq!(
+            detect_ts_unit(&bars_at(&[1.7e12, 1.7e12 + 6e4])),
+            TsUnit::Millis
+        );
+        assert_eq!(detect_ts_unit(&[]), TsUnit::Millis);
+    }
+
+    #[test]
+    fn normalize_seconds_to_millis() {
+        let mut data = bars_at(&[1.7e9, 1.7e9 + 60.0]);
+        normalize_ts_impl(&mut data, true);
+        assert_eq!(data[0].ts, 1.7e12);
+        assert_eq!(data[1].ts, 1.7e12 + 6e4);
+        // Already millis: a second pass is a no-op.
+        normalize_ts_impl(&mut data, true);
+        assert_eq!(data[0].ts, 1.7e12);
+    }
+
+    #[test]
+    fn normalize_millis_to_seconds_and_scale() {
+        let mut data = bars_at(&[1.7e12]);
+        normalize_ts_impl(&mut data, false);
+        assert!((data[0].ts - 1.7e9).abs() < 1e-3);
+
+        let mut data = bars_at(&[1.0, 2.0]);
+        scale_timestamps_impl(&mut data, 10.0);
+        assert_eq!(data[0].ts, 10.0);
+        assert_eq!(data[1].ts, 20.0);
+    }
+
+    // -----------------------------------------------------------------------
+    // VWAP skipping leading zero-volume bars
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn vwap_skip_zero_leading_bars() {
+        let mut data = vec![
+            pp(1.0, 0.0, 11.0, 9.0, 10.0, 0.0),
+            pp(2.0, 0.0, 11.0, 9.0, 10.0, 0.0),
+            pp(3.0, 0.0, 11.0, 9.0, 10.0, 0.0),
+        ];
+        data.extend(sample_prices().into_iter().map(|mut p| {
+            p.ts += 3.0;
+            p
+        }));
+
+        let result = calc_vwap_skip_zero_impl(&data);
+        assert_eq!(result.len(), data.len() - 3);
+        assert_eq!(result[0].ts, 4.0);
+        assert!(result.iter().all(|p| p.value > 0.0));
+        assert_eq!(result, calc_vwap_impl(&data)[3..].to_vec());
+    }
+
+    #[test]
+    fn vwap_skip_zero_all_zero_volume() {
+        let data = vec![pp(1.0, 0.0, 11.0, 9.0, 10.0, 0.0)];
+        assert!(calc_vwap_skip_zero_impl(&data).is_empty());
+        assert_eq!(
+            calc_vwap_skip_zero_impl(&sample_prices()),
+            calc_vwap_impl(&sample_prices())
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Streaming LTTB
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn lttb_stream_exact_below_threshold() {
+        let mut stream = LttbStream::new(10);
+        let data: Vec<DataPoint> = (0..10).map(|i| dp(i as f64, (i * 3 % 7) as f64)).collect();
+        for p in &data {
+            stream.push(p.clone());
+        }
+        assert_eq!(stream.count(), 10);
+        assert_eq!(stream.snapshot(), data);
+    }
+
+    #[test]
+    fn lttb_stream_respects_threshold() {
+        let mut stream = LttbStream::new(50);
+        for i in 0..10_000 {
+            stream.push(dp(i as f64, (i as f64 / 100.0).sin()));
+            assert!(stream.snapshot().len() <= 50);
+        }
+        let snap = stream.snapshot();
+        assert!(snap.len() >= 25);
+        assert_eq!(snap[0].ts, 0.0);
+        assert_eq!(snap[snap.len() - 1].ts, 9999.0);
+        assert!(snap.windows(2).all(|w| w[0].ts < w[1].ts));
+    }
+
+    #[test]
+    fn lttb_stream_respects_small_thresholds() {
+        for threshold in [0, 1, 2, 3, 4, 5] {
+            let mut stream = LttbStream::new(threshold);
+            for i in 0..500 {
+                stream.push(dp(i as f64, (i as f64 / 7.0).sin()));
+                let snap = stream.snapshot();
+                assert!(
+                    snap.len() <= threshold.max(3),
+                    "threshold {} after {} pushes: {}",
+                    threshold,
+                    i + 1,
+                    snap.len()
+                );
+                assert!(snap.windows(2).all(|w| w[0].ts < w[1].ts));
+            }
+        }
+    }
+
+    #[test]
+    fn lttb_stream_keeps_shape() {
+        let mut stream = LttbStream::new(100);
+        let data: Vec<DataPoint> = (0..5000)
+            .map(|i| dp(i as f64, (i as f64 / 250.0).sin()))
+            .collect();
+        for p in &data {
+            stream.push(p.clone());
+        }
+        let snap = stream.snapshot();
+        // Peaks and troughs of the ~3 sine cycles survive the decimation.
+        let max = snap.iter().map(|p| p.value).fold(f64::MIN, f64::max);
+        let min = snap.iter().map(|p| p.value).fold(f64::MAX, f64::min);
+        assert!(max > 0.95, "max {}", max);
+        assert!(min < -0.95, "min {}", min);
+        // And the time axis is covered roughly evenly.
+        let widest_gap = snap
+            .windows(2)
+            .map(|w| w[1].ts - w[0].ts)
+            .fold(0.0, f64::max);
+        assert!(widest_gap < 5000.0 / 10.0, "gap {}", widest_gap);
+    }
+
+    // -----------------------------------------------------------------------
+    // Range-restricted SMA
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn sma_range_uses_history_for_first_value() {
+        let data = sample_prices();
+        let full = calc_sma_impl(&data, 3);
+        let result = calc_sma_range_impl(&data, 3, 5, 8);
+        // Bars 5, 6, 7 correspond to full[3..6].
+        assert_eq!(result, full[3..6].to_vec());
+    }
+
+    #[test]
+    fn sma_range_clips_warmup_and_end() {
+        let data = sample_prices();
+        let full = calc_sma_impl(&data, 3);
+        // Bars 0 and 1 have no SMA; bar 2 is the first.
+        assert_eq!(calc_sma_range_impl(&data, 3, 0, 4), full[..2].to_vec());
+        assert_eq!(calc_sma_range_impl(&data, 3, 8, 100), full[6..].to_vec());
+    }
+
+    #[test]
+    fn sma_range_empty_cases() {
+        let data = sample_prices();
+        assert!(calc_sma_range_impl(&data, 3, 5, 5).is_empty());
+        assert!(calc_sma_range_impl(&data, 3, 0, 2).is_empty());
+        assert!(calc_sma_range_impl(&data, 0, 0, 10).is_empty());
+        assert!(calc_sma_range_impl(&data, 3, 20, 30).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // LTTB tie-breaking
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn lttb_exact_tie_prefers_earlier_index() {
+        // Points 1 and 2 form equal-area triangles with (0, 0) and the next
+        // bucket's average (4, 0).
+        let data = vec![
+            dp(0.0, 0.0),
+            dp(1.0, 1.0),
+            dp(2.0, -1.0),
+            dp(3.0, 0.0),
+            dp(4.0, 0.0),
+        ];
+        let result = lttb_downsample_impl(&data, 3);
+        let ts: Vec<f64> = result.iter().map(|p| p.ts).collect();
+        assert_eq!(ts, vec![0.0, 1.0, 4.0]);
+    }
+
+    #[test]
+    fn lttb_near_tie_prefers_earlier_index() {
+        // Point 2's area exceeds point 1's only by rounding-level noise.
+        let data = vec![
+            dp(0.0, 0.0),
+            dp(1.0, 1.0),
+            dp(2.0, -1.000_000_000_000_1),
+            dp(3.0, 0.0),
+            dp(4.0, 0.0),
+        ];
+        let result = lttb_downsample_impl(&data, 3);
+        assert_eq!(result[1].ts, 1.0);
+    }
+
+    #[test]
+    fn lttb_tie_margin_scales_with_tiny_values() {
+        // Point 2's area is twice point 1's; both are far below 1e-9, so an
+        // absolute margin would wrongly call it a tie.
+        let data = vec![
+            dp(0.0, 0.0),
+            dp(1.0, 1e-12),
+            dp(2.0, -2e-12),
+            dp(3.0, 0.0),
+            dp(4.0, 0.0),
+        ];
+        assert_eq!(lttb_downsample_impl(&data, 3)[1].ts, 2.0);
+        assert_eq!(lttb_downsample_time_impl(&data, 3)[1].ts, 2.0);
+    }
+
+    #[test]
+    fn lttb_area_is_stable_at_epoch_ms_timestamps() {
+        // Points 1 and 2 tie exactly against the mean of points 4..=6. Raw
+        // epoch-ms sums round that mean at ~1e-4 ms, which breaks the tie
+        // toward point 2; offsets from the anchor keep it exact.
+        let base = 1_700_000_000_000.0;
+        let data: Vec<DataPoint> = [
+            (0.0, 0.0),
+            (59_021.0, -154.0),
+            (103_749.0, -251.0),
+            (169_374.0, -350.0),
+            (238_838.0, -877.0),
+            (296_511.0, 2608.0),
+            (359_211.0, -3671.0),
+            (420_000.0, 0.0),
+        ]
+        .iter()
+        .map(|&(dt, v)| dp(base + dt, v))
+        .collect();
+
+        let naive_area = |p: &DataPoint| {
+            let next = &data[4..7];
+            let avg_ts = next.iter().map(|q| q.ts).sum::<f64>() / 3.0;
+            let avg_val = next.iter().map(|q| q.value).sum::<f64>() / 3.0;
+            let a = &data[0];
+            ((a.ts - avg_ts) * (p.value - a.value) - (a.ts - p.ts) * (avg_val - a.value)).abs()
+        };
+        let (n1, n2) = (naive_area(&data[1]), naive_area(&data[2]));
+        assert!(n2 > n1 + LTTB_TIE_EPSILON * n1);
+
+        assert_eq!(lttb_downsample_indices_impl(&data, 4)[1], 1);
+    }
+
+    #[test]
+    fn lttb_pinned_indices_for_tie_fixture() {
+        // Zig-zag fixture; indices are pinned so any change to the area
+        // arithmetic or tie handling shows up as a diff here.
+        let data: Vec<DataPoint> = (0..21)
+            .map(|i| dp(i as f64, if i % 2 == 0 { 0.0 } else { 1.0 }))
+            .collect();
+        let ts: Vec<f64> = lttb_downsample_impl(&data, 6)
+            .iter()
+            .map(|p| p.ts)
+            .collect();
+        assert_eq!(ts, vec![0.0, 1.0, 6.0, 11.0, 19.0, 20.0]);
+    }
+
+    // -----------------------------------------------------------------------
+    // Gap detection
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn find_gaps_well_formed_data() {
+        let data = bars_at(&[0.0, 60_000.0, 120_000.0, 180_000.0]);
+        assert!(find_gaps_impl(&data, 60_000.0, 0.1).is_empty());
+    }
+
+    #[test]
+    fn find_gaps_reports_holes() {
+        let data = bars_at(&[0.0, 60_000.0, 240_000.0, 300_000.0, 370_000.0]);
+        let gaps = find_gaps_impl(&data, 60_000.0, 0.1);
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(
+            gaps[0],
+            Gap {
+                start_ts: 60_000.0,
+                end_ts: 240_000.0,
+                missing_bars: 2,
+            }
+        );
+        // 70s spacing exceeds 66s but rounds to a single interval.
+        assert_eq!(gaps[1].missing_bars, 1);
+    }
+
+    #[test]
+    fn find_gaps_tolerance_and_bad_interval() {
+        let data = bars_at(&[0.0, 60_000.0, 130_000.0]);
+        assert!(find_gaps_impl(&data, 60_000.0, 0.2).is_empty());
+        assert!(find_gaps_impl(&data, 0.0, 0.1).is_empty());
+        assert!(find_gaps_impl(&data, f64::NAN, 0.1).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Outlier detection
+    // -----------------------------------------------------------------------
+
+    fn spiked_prices() -> Vec<PricePoint> {
+        let mut data = rising_prices(30);
+        data[15].close *= 10.0;
+        data
+    }
+
+    #[test]
+    fn outliers_flag_injected_spike() {
+        let data = spiked_prices();
+        assert_eq!(detect_outliers_impl(&data, 10, 3.0), vec![15]);
+        assert!(detect_outliers_impl(&rising_prices(30), 10, 3.0).is_empty());
+    }
+
+    #[test]
+    fn outliers_cleaned_with_median() {
+        let data = spiked_prices();
+        let cleaned = clean_outliers_impl(&data, 10, 3.0);
+        // Neighbours 10..=20 minus 15 have median (114 + 116) / 2.
+        assert_eq!(cleaned[15].close, 115.0);
+        assert_eq!(cleaned[14], data[14]);
+        assert_eq!(cleaned.len(), data.len());
+    }
+
+    #[test]
+    fn outliers_edges_and_degenerate_inputs() {
+        let mut data = rising_prices(30);
+        data[0].close = 1000.0;
+        assert_eq!(detect_outliers_impl(&data, 6, 3.0), vec![0]);
+        assert!(detect_outliers_impl(&data, 1, 3.0).is_empty());
+        assert!(detect_outliers_impl(&data[..2], 6, 3.0).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // EMA seeding
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn ema_seeded_sma_window_matches_default() {
+        let data = sample_prices();
+        assert_eq!(
+            calc_ema_seeded_impl(&data, 4, EmaSeed::SmaWindow),
+            calc_ema_impl(&data, 4)
+        );
+        assert_eq!(EmaSeed::from_name("FIRST"), Some(EmaSeed::FirstValue));
+        assert_eq!(EmaSeed::from_name("bogus"), None);
+    }
+
+    #[test]
+    fn ema_seeded_first_value_emits_every_bar() {
+        let data = sample_prices();
+        let ema = calc_ema_seeded_impl(&data, 4, EmaSeed::FirstValue);
+        assert_eq!(ema.len(), data.len());
+        assert_eq!(ema[0].value, 11.0);
+        assert_eq!(ema[0].ts, data[0].ts);
+        assert!((ema[1].value - (12.0 * 0.4 + 11.0 * 0.6)).abs() < 1e-12);
+        assert!(calc_ema_seeded_impl(&data, 0, EmaSeed::FirstValue).is_empty());
+        assert!(calc_ema_seeded_impl(&[], 4, EmaSeed::FirstValue).is_empty());
+    }
+
+    #[test]
+    fn ema_seeds_converge() {
+        let mut data = rising_prices(200);
+        data[0].close = 0.0;
+        let sma_seeded = calc_ema_seeded_impl(&data, 10, EmaSeed::SmaWindow);
+        let first_seeded = calc_ema_seeded_impl(&data, 10, EmaSeed::FirstValue);
+        let early = (sma_seeded[0].value - first_seeded[9].value).abs();
+        let late = (sma_seeded.last().unwrap().value - first_seeded.last().unwrap().value).abs();
+        assert!(early > 1.0);
+        assert!(late < 1e-9);
+    }
+
+    // -----------------------------------------------------------------------
+    // MA distance
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn ma_distance_sma() {
+        let data = sample_prices();
+        let dist = calc_ma_distance_impl(&data, 3, MaKind::Sma);
+        assert_eq!(dist.len(), 8);
+        // Closes 11, 12, 13: SMA 12, distance 100 * 1 / 12.
+        assert_eq!(dist[0].ts, data[2].ts);
+        assert!((dist[0].value - 100.0 / 12.0).abs() < 1e-9);
+        assert!(dist.last().unwrap().value < 0.0);
+    }
+
+    #[test]
+    fn ma_distance_ema_and_guards() {
+        let data = sample_prices();
+        let ema = calc_ema_impl(&data, 4);
+        let dist = calc_ma_distance_impl(&data, 4, MaKind::Ema);
+        assert_eq!(dist.len(), ema.len());
+        let expected = 100.0 * (data[9].close - ema[6].value) / ema[6].value;
+        assert!((dist[6].value - expected).abs() < 1e-9);
+        assert!(calc_ma_distance_impl(&data, 11, MaKind::Sma).is_empty());
+        assert!(calc_ma_distance_impl(&data, 0, MaKind::Ema).is_empty());
+
+        let flat: Vec<PricePoint> = (0..5)
+            .map(|i| pp(i as f64, 0.0, 0.0, 0.0, 0.0, 1.0))
+            .collect();
+        let dist = calc_ma_distance_impl(&flat, 2, MaKind::Sma);
+        assert_eq!(dist.len(), 4);
+        assert!(dist.iter().all(|p| p.value.is_nan()));
+    }
+
+    // -----------------------------------------------------------------------
+    // Rolling VWAP
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn rolling_vwap_window() {
+        let data = vec![
+            pp(1.0, 0.0, 10.0, 10.0, 10.0, 1.0),
+            pp(2.0, 0.0, 20.0, 20.0, 20.0, 3.0),
+            pp(3.0, 0.0, 30.0, 30.0, 30.0, 1.0),
+        ];
+        let vwap = calc_rolling_vwap_impl(&data, 2);
+        assert_eq!(vwap.len(), 2);
+        assert_eq!(vwap[0].ts, 2.0);
+        assert!((vwap[0].value - 17.5).abs() < 1e-12);
+        assert!((vwap[1].value - 22.5).abs() < 1e-12);
+
+        // A full-length window equals the last cumulative VWAP.
+        let sample = sample_prices();
+        let full = calc_rolling_vwap_impl(&sample, sample.len());
+        let cumulative = calc_vwap_impl(&sample);
+        assert!((full[0].value - cumulative.last().unwrap().value).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rolling_vwap_zero_volume_and_guards() {
+        let data = vec![
+            pp(1.0, 0.0, 10.0, 10.0, 10.0, 0.0),
+            pp(2.0, 0.0, 20.0, 20.0, 20.0, 0.0),
+        ];
+        assert_eq!(calc_rolling_vwap_impl(&data, 2)[0].value, 15.0);
+        assert!(calc_rolling_vwap_impl(&data, 0).is_empty());
+        assert!(calc_rolling_vwap_impl(&data, 3).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Expression evaluator
+    // -----------------------------------------------------------------------
+
+    fn assert_points_close(actual: &[IndicatorPoint], expected: &[IndicatorPoint]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert_eq!(a.ts, e.ts);
+            assert!((a.value - e.value).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn expression_matches_builtins() {
+        let data = sample_prices();
+        assert_points_close(
+            &eval_expression_impl(&data, "sma(close, 3)").unwrap(),
+            &calc_sma_impl(&data, 3),
+        );
+        assert_points_close(
+            &eval_expression_impl(&data, "EMA(close,4)").unwrap(),
+            &calc_ema_impl(&data, 4),
+        );
+    }
+
+    #[test]
+    fn expression_arithmetic_and_nesting() {
+        let data = sample_prices();
+        let spread = eval_expression_impl(&data, "ema(close, 2) - sma(close, 4)").unwrap();
+        assert_eq!(spread.len(), 7);
+        assert_eq!(spread[0].ts, data[3].ts);
+        let ema = calc_ema_impl(&data, 2);
+        let sma = calc_sma_impl(&data, 4);
+        assert!((spread[0].value - (ema[2].value - sma[0].value)).abs() < 1e-9);
+
+        let mid = eval_expression_impl(&data, "(high + low) / 2 * -1").unwrap();
+        assert_eq!(mid[0].value, -(data[0].high + data[0].low) / 2.0);
+
+        let smoothed = eval_expression_impl(&data, "sma(sma(close, 2), 2)").unwrap();
+        assert_eq!(smoothed.len(), 8);
+        assert_eq!(smoothed[0].value, 12.0);
+    }
+
+    #[test]
+    fn expression_division_by_zero_skips_bar() {
+        let data = vec![
+            pp(1.0, 0.0, 1.0, 1.0, 1.0, 0.0),
+            pp(2.0, 0.0, 1.0, 1.0, 1.0, 2.0),
+        ];
+        let out = eval_expression_impl(&data, "close / volume").unwrap();
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].value, 0.5);
+    }
+
+    #[test]
+    fn expression_errors() {
+        let data = sample_prices();
+        assert!(eval_expression_impl(&data, "wma(close, 3)")
+            .unwrap_err()
+            .contains("unknown function"));
+        assert!(eval_expression_impl(&data, "vwap")
+            .unwrap_err()
+            .contains("unknown series"));
+        assert!(eval_expression_impl(&data, "sma(close, 2.5)").is_err());
+        assert!(eval_expression_impl(&data, "sma(close)").is_err());
+        assert!(eval_expression_impl(&data, "close +").is_err());
+        assert!(eval_expression_impl(&data, "close close").is_err());
+        assert!(eval_expression_impl(&data, "close % 2").is_err());
+    }
+
+    #[test]
+    fn expression_huge_period_is_empty_not_overflow() {
+        let data = sample_prices();
+        // Saturates to `usize::MAX` on the way through `as usize`.
+        let period = "1".repeat(30);
+        for func in ["ema", "sma"] {
+            let src = format!("{}(close, {})", func, period);
+            assert_eq!(eval_expression_impl(&data, &src), Ok(Vec::new()));
+        }
+    }
+
+    #[test]
+    fn expression_nesting_is_bounded() {
+        let data = sample_prices();
+        let deep = format!("{}close{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert!(eval_expression_impl(&data, &deep)
+            .unwrap_err()
+            .contains("too deeply nested"));
+        let negs = format!("{}close", "-".repeat(100_000));
+        assert!(eval_expression_impl(&data, &negs).is_err());
+        let chain = vec!["close"; 100_000].join(" + ");
+        assert!(eval_expression_impl(&data, &chain).is_err());
+        // Modest nesting still evaluates.
+        assert!(eval_expression_impl(&data, "((close + 1) * (open - 1)) / -(-high)").is_ok());
+    }
+
+    // -----------------------------------------------------------------------
+    // Series normalization
+    // -----------------------------------------------------------------------
+
+    fn ip(ts: f64, value: f64) -> IndicatorPoint {
+        IndicatorPoint { ts, value }
+    }
+
+    #[test]
+    fn normalize_min_max() {
+        let points = vec![ip(1.0, 20.0), ip(2.0, 80.0), ip(3.0, 50.0)];
+        let norm = normalize_series_impl(&points, NormMode::MinMax);
+        let values: Vec<f64> = norm.iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![0.0, 1.0, 0.5]);
+        assert_eq!(norm[2].ts, 3.0);
+
+        let flat = vec![ip(1.0, 7.0), ip(2.0, 7.0)];
+        assert!(normalize_series_impl(&flat, NormMode::MinMax)
+            .iter()
+            .all(|p| p.value == 0.5));
+        assert!(normalize_series_impl(&[], NormMode::MinMax).is_empty());
+    }
+
+    #[test]
+    fn normalize_z_score() {
+        let points = vec![ip(1.0, 2.0), ip(2.0, 4.0), ip(3.0, 6.0)];
+        let norm = normalize_series_impl(&points, NormMode::ZScore);
+        let std_dev = (8.0f64 / 3.0).sqrt();
+        assert!((norm[0].value + 2.0 / std_dev).abs() < 1e-12);
+        assert_eq!(norm[1].value, 0.0);
+
+        let flat = vec![ip(1.0, 7.0), ip(2.0, 7.0)];
+        assert!(normalize_series_impl(&flat, NormMode::ZScore)
+            .iter()
+            .all(|p| p.value == 0.0));
+        assert_eq!(NormMode::from_name("ZScore"), Some(NormMode::ZScore));
+    }
+
+    // -----------------------------------------------------------------------
+    // Series order
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn ensure_ascending_reverses_newest_first() {
+        let data = sample_prices();
+        let reversed: Vec<PricePoint> = data.iter().rev().cloned().collect();
+        assert_eq!(ensure_ascending_impl(&reversed), data);
+        assert_eq!(ensure_ascending_impl(&data), data);
+        assert!(ensure_ascending_impl(&[]).is_empty());
+        assert_eq!(ensure_ascending_impl(&data[..1]), data[..1].to_vec());
+    }
+
+    // -----------------------------------------------------------------------
+    // Series alignment
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn align_inner_partial_overlap() {
+        let a = bars_at(&[1.0, 2.0, 3.0, 5.0]);
+        let b = bars_at(&[2.0, 3.0, 4.0, 5.0, 6.0]);
+        let joined = align_series_impl(&a, &b, JoinKind::Inner);
+        let ts: Vec<f64> = joined.iter().map(|(x, _)| x.as_ref().unwrap().ts).collect();
+        assert_eq!(ts, vec![2.0, 3.0, 5.0]);
+        assert!(joined
+            .iter()
+            .all(|(x, y)| y.as_ref().unwrap().ts == x.as_ref().unwrap().ts));
+        assert!(align_series_impl(&a, &[], JoinKind::Inner).is_empty());
+    }
+
+    #[test]
+    fn align_outer_partial_overlap() {
+        let a = bars_at(&[1.0, 2.0, 3.0, 5.0]);
+        let b = bars_at(&[2.0, 3.0, 4.0, 5.0, 6.0]);
+        let joined = align_series_impl(&a, &b, JoinKind::Outer);
+        let shape: Vec<(bool, bool)> = joined
+            .iter()
+            .map(|(x, y)| (x.is_some(), y.is_some()))
+            .collect();
+        assert_eq!(
+            shape,
+            vec![
+                (true, false),
+                (true, true),
+                (true, true),
+                (false, true),
+                (true, true),
+                (false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn align_closes_pairs_shared_bars() {
+        let a = sample_prices();
+        let b: Vec<PricePoint> = a[3..]
+            .iter()
+            .map(|p| PricePoint {
+                close: p.close * 2.0,
+                ..p.clone()
+            })
+            .collect();
+        let pairs = align_closes_impl(&a, &b);
+        assert_eq!(pairs.len(), 7);
+        assert_eq!(pairs[0].ts, a[3].ts);
+        assert_eq!(pairs[0].b, 2.0 * pairs[0].a);
+    }
+
+    // -----------------------------------------------------------------------
+    // Ratio / spread
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn ratio_of_identical_series_is_one() {
+        let data = sample_prices();
+        let ratio = calc_ratio_impl(&data, &data);
+        assert_eq!(ratio.len(), data.len());
+        assert!(ratio.iter().all(|p| p.value == 1.0));
+    }
+
+    #[test]
+    fn ratio_zero_denominator_is_nan() {
+        let a = sample_prices();
+        let mut b = sample_prices();
+        b[2].close = 0.0;
+        let ratio = calc_ratio_impl(&a, &b);
+        assert_eq!(ratio.len(), a.len());
+        assert_eq!(ratio[2].ts, a[2].ts);
+        assert!(ratio[2].value.is_nan());
+        assert!(ratio.iter().filter(|p| p.value.is_nan()).count() == 1);
+    }
+
+    #[test]
+    fn spread_with_hedge_ratio() {
+        let a = sample_prices();
+        let b: Vec<PricePoint> = a[5..].to_vec();
+        let spread = calc_spread_impl(&a, &b, 0.5);
+        assert_eq!(spread.len(), 5);
+        assert_eq!(spread[0].ts, a[5].ts);
+        assert_eq!(spread[0].value, a[5].close * 0.5);
+        assert!(calc_spread_impl(&a, &a, 1.0).iter().all(|p| p.value == 0.0));
+    }
+
+    // -----------------------------------------------------------------------
+    // Rolling z-score
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn zscore_trailing_window() {
+        let points = vec![ip(1.0, 2.0), ip(2.0, 4.0), ip(3.0, 6.0), ip(4.0, 6.0)];
+        let z = calc_zscore_impl(&points, 3);
+        assert_eq!(z.len(), 2);
+        assert_eq!(z[0].ts, 3.0);
+        assert!((z[0].value - 2.0 / (8.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        // Window 4, 6, 6: mean 16/3, population stddev sqrt(8/9).
+        assert!((z[1].value - (2.0 / 3.0) / (8.0f64 / 9.0).sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn zscore_flat_and_guards() {
+        let flat = vec![ip(1.0, 5.0), ip(2.0, 5.0), ip(3.0, 5.0)];
+        assert_eq!(calc_zscore_impl(&flat, 2)[1].value, 0.0);
+        assert!(calc_zscore_impl(&flat, 0).is_empty());
+        assert!(calc_zscore_impl(&flat, 4).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Uniform sampling
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn uniform_sample_even_indices() {
+        let data: Vec<DataPoint> = (0..11).map(|i| dp(i as f64, i as f64)).collect();
+        let ts: Vec<f64> = uniform_sample_impl(&data, 5).iter().map(|p| p.ts).collect();
+        assert_eq!(ts, vec![0.0, 2.0, 5.0, 7.0, 10.0]);
+        let ts: Vec<f64> = uniform_sample_impl(&data, 2).iter().map(|p| p.ts).collect();
+        assert_eq!(ts, vec![0.0, 10.0]);
+    }
+
+    #[test]
+    fn uniform_sample_small_inputs() {
+        let data: Vec<DataPoint> = (0..4).map(|i| dp(i as f64, i as f64)).collect();
+        assert_eq!(uniform_sample_impl(&data, 4), data);
+        assert_eq!(uniform_sample_impl(&data, 10), data);
+        assert!(uniform_sample_impl(&data, 0).is_empty());
+        assert_eq!(uniform_sample_impl(&data, 1), vec![data[0].clone()]);
+    }
+
+    // -----------------------------------------------------------------------
+    // Integer timestamps
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn lttb_i_preserves_exact_timestamps() {
+        // Above 2^53 adjacent i64 values collapse to the same f64.
+        let base: i64 = (1 << 53) + 1;
+        let data: Vec<DataPointI> = (0..20)
+            .map(|i| DataPointI {
+                ts: base + i,
+                value: ((i * 7) % 5) as f64,
+            })
+            .collect();
+        let out = lttb_downsample_i_impl(&data, 6);
+        assert_eq!(out.len(), 6);
+        assert_eq!(out[0].ts, base);
+        assert_eq!(out[5].ts, base + 19);
+        assert!(out.iter().all(|p| data.contains(p)));
+    }
+
+    #[test]
+    fn sma_i_matches_float_version() {
+        let data = sample_prices();
+        let ints: Vec<PricePointI> = data
+            .iter()
+            .map(|p| PricePointI {
+                ts: 1_700_000_000_123 + p.ts as i64,
+                open: p.open,
+                high: p.high,
+                low: p.low,
+                close: p.close,
+                volume: p.volume,
+            })
+            .collect();
+        let sma = calc_sma_i_impl(&ints, 3);
+        let expected = calc_sma_impl(&data, 3);
+        assert_eq!(sma.len(), expected.len());
+        assert_eq!(sma[0].ts, ints[2].ts);
+        assert!(sma.iter().zip(&expected).all(|(a, b)| a.value == b.value));
+        assert!(calc_sma_i_impl(&ints, 0).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Output rounding
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn round_to_decimals() {
+        let mut points = vec![ip(1.0, 12.34567), ip(2.0, -12.34567), ip(3.0, 2.5)];
+        round_to(&mut points, 2);
+        assert_eq!(points[0].value, 12.35);
+        assert_eq!(points[1].value, -12.35);
+        assert_eq!(points[0].ts, 1.0);
+        round_to(&mut points, 0);
+        assert_eq!(points[2].value, 3.0);
+    }
+
+    #[test]
+    fn round_value_passes_through_non_finite() {
+        assert_eq!(round_value(1e308, 10), 1e308);
+        assert!(round_value(f64::NAN, 2).is_nan());
+        assert_eq!(round_value(f64::INFINITY, 2), f64::INFINITY);
+    }
+
+    // -----------------------------------------------------------------------
+    // Duplicate timestamps
+    // -----------------------------------------------------------------------
+
+    fn triple_duplicate() -> Vec<PricePoint> {
+        vec![
+            pp(1.0, 10.0, 11.0, 9.0, 10.5, 100.0),
+            pp(2.0, 10.5, 12.0, 10.0, 11.0, 200.0),
+            pp(2.0, 11.0, 13.0, 10.5, 12.0, 300.0),
+            pp(2.0, 12.0, 12.5, 9.5, 11.5, 400.0),
+            pp(3.0, 11.5, 12.0, 11.0, 11.8, 150.0),
+        ]
+    }
+
+    #[test]
+    fn dedupe_keep_first_and_last() {
+        let data = triple_duplicate();
+        let first = dedupe_timestamps_impl(&data, DedupePolicy::KeepFirst);
+        assert_eq!(
+            first,
+            vec![data[0].clone(), data[1].clone(), data[4].clone()]
+        );
+        let last = dedupe_timestamps_impl(&data, DedupePolicy::KeepLast);
+        assert_eq!(
+            last,
+            vec![data[0].clone(), data[3].clone(), data[4].clone()]
+        );
+        assert_eq!(
+            dedupe_timestamps_impl(&sample_prices(), DedupePolicy::Merge),
+            sample_prices()
+        );
+    }
+
+    #[test]
+    fn dedupe_merge_triple() {
+        let merged = dedupe_timestamps_impl(&triple_duplicate(), DedupePolicy::Merge);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[1], pp(2.0, 10.5, 13.0, 9.5, 11.5, 900.0));
+        assert!(dedupe_timestamps_impl(&[], DedupePolicy::Merge).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // SMA ribbon
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn sma_multi_matches_individual_calls() {
+        let mut data = rising_prices(120);
+        for (i, p) in data.iter_mut().enumerate() {
+            p.close += ((i * 37) % 11) as f64 * 0.13;
+        }
+        let periods = [10, 20, 30, 50, 100, 1];
+        let ribbon = calc_sma_multi_impl(&data, &periods);
+        assert_eq!(ribbon.len(), periods.len());
+        for (series, &period) in ribbon.iter().zip(&periods) {
+            assert_eq!(*series, calc_sma_impl(&data, period));
+        }
+    }
+
+    #[test]
+    fn sma_multi_invalid_periods_are_empty() {
+        let data = sample_prices();
+        let ribbon = calc_sma_multi_impl(&data, &[0, 3, 11]);
+        assert!(ribbon[0].is_empty());
+        assert_eq!(ribbon[1], calc_sma_impl(&data, 3));
+        assert!(ribbon[2].is_empty());
+        assert!(calc_sma_multi_impl(&data, &[]).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // MA crosses
+    // -----------------------------------------------------------------------
+
+    /// Closes falling 10 bars, rising 10, then falling 10 again.
+    fn zigzag_prices() -> Vec<PricePoint> {
+        (0..30)
+            .map(|i| {
+                let close = match i {
+                    0..=9 => 120.0 - i as f64,
+                    10..=19 => 110.0 + (i - 10) as f64 * 2.0,
+                    _ => 128.0 - (i - 20) as f64 * 2.0,
+                };
+                pp(i as f64, close, close + 1.0, close - 1.0, close, 1000.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ma_cross_golden_then_death() {
+        let data = zigzag_prices();
+        let events = detect_ma_cross_impl(&data, 2, 5, MaKind::Sma);
+        let kinds: Vec<SignalKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![SignalKind::GoldenCross, SignalKind::DeathCross]);
+        let golden = &events[0];
+        assert!(golden.index > 10 && golden.index < 15);
+        assert_eq!(golden.ts, data[golden.index].ts);
+        let fast = calc_sma_impl(&data, 2)[golden.index - 1].value;
+        let slow = calc_sma_impl(&data, 5)[golden.index - 4].value;
+        assert!(fast > slow);
+        assert!((golden.strength - (fast - slow) / slow).abs() < 1e-12);
+        assert!(events[1].index > 20);
+    }
+
+    #[test]
+    fn ma_cross_ema_and_guards() {
+        let data = zigzag_prices();
+        assert_eq!(detect_ma_cross_impl(&data, 2, 5, MaKind::Ema).len(), 2);
+        assert!(detect_ma_cross_impl(&rising_prices(30), 2, 5, MaKind::Sma).is_empty());
+        assert!(detect_ma_cross_impl(&data, 0, 5, MaKind::Sma).is_empty());
+        assert!(detect_ma_cross_impl(&data, 2, 31, MaKind::Sma).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Volume profile
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn volume_profile_spreads_by_overlap() {
+        let data = vec![
+            pp(1.0, 0.0, 14.0, 10.0, 12.0, 400.0),
+            pp(2.0, 0.0, 12.0, 11.0, 11.5, 100.0),
+            pp(3.0, 0.0, 13.0, 13.0, 13.0, 50.0),
+        ];
+        let profile = calc_volume_profile_impl(&data, 4);
+        assert_eq!(profile.bin_size, 1.0);
+        assert_eq!(profile.prices, vec![10.5, 11.5, 12.5, 13.5]);
+        assert_eq!(profile.volumes, vec![100.0, 200.0, 100.0, 150.0]);
+        assert_eq!(profile.poc_index, 1);
+        assert_eq!(profile.poc_price, 11.5);
+        let total: f64 = profile.volumes.iter().sum();
+        assert_eq!(total, 550.0);
+    }
+
+    #[test]
+    fn volume_profile_degenerate_inputs() {
+        assert_eq!(calc_volume_profile_impl(&[], 10), VolumeProfile::default());
+        assert_eq!(
+            calc_volume_profile_impl(&sample_prices(), 0),
+            VolumeProfile::default()
+        );
+
+        let flat = vec![
+            pp(1.0, 5.0, 5.0, 5.0, 5.0, 10.0),
+            pp(2.0, 5.0, 5.0, 5.0, 5.0, 20.0),
+        ];
+        let profile = calc_volume_profile_impl(&flat, 3);
+        assert_eq!(profile.volumes, vec![30.0, 0.0, 0.0]);
+        assert_eq!(profile.poc_price, 5.0);
+    }
+
+    // -----------------------------------------------------------------------
+    // Value area
+    // -----------------------------------------------------------------------
+
+    fn profile_of(volumes: Vec<f64>, poc_index: usize) -> VolumeProfile {
+        VolumeProfile {
+            prices: (0..volumes.len()).map(|i| 100.5 + i as f64).collect(),
+            volumes,
+            bin_size: 1.0,
+            poc_index,
+            poc_price: 100.5 + poc_index as f64,
+        }
+    }
+
+    #[test]
+    fn value_area_expands_toward_larger_neighbour() {
+        // Total 100, target 70: POC 40, then 20 above, then 15 below.
+        let profile = profile_of(vec![5.0, 15.0, 40.0, 20.0, 10.0, 10.0], 2);
+        let area = calc_value_area_impl(&profile, 0.70);
+        assert_eq!(area.low, 101.0);
+        assert_eq!(area.high, 104.0);
+        assert_eq!(area.volume, 75.0);
+    }
+
+    #[test]
+    fn value_area_edges_and_empty() {
+        let profile = profile_of(vec![50.0, 30.0, 20.0], 0);
+        let area = calc_value_area_impl(&profile, 1.0);
+        assert_eq!((area.low, area.high, area.volume), (100.0, 103.0, 100.0));
+        assert_eq!(
+            calc_value_area_impl(&VolumeProfile::default(), 0.7),
+            ValueArea::default()
+        );
+
+        // A profile built in JS with mismatched arrays must not index past
+        // `prices`.
+        let mut short = profile_of(vec![5.0, 40.0, 20.0], 1);
+        short.prices.truncate(1);
+        assert_eq!(calc_value_area_impl(&short, 0.7), ValueArea::default());
+
+        let real = calc_volume_profile_impl(&sample_prices(), 8);
+        let area = calc_value_area_impl(&real, 0.7);
+        assert!(area.low <= real.poc_price && real.poc_price <= area.high);
+    }
+
+    // -----------------------------------------------------------------------
+    // Multi-horizon change
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn multi_horizon_change() {
+        let data = sample_prices();
+        let changes = calc_multi_horizon_change_impl(&data, &[1, 5, 9, 10, 0]);
+        // Last close 10; one bar earlier 11, five earlier 15, nine earlier 11.
+        assert!((changes[0] - 100.0 * (10.0 - 11.0) / 11.0).abs() < 1e-12);
+        assert!((changes[1] - 100.0 * (10.0 - 15.0) / 15.0).abs() < 1e-12);
+        assert!((changes[2] - 100.0 * (10.0 - 11.0) / 11.0).abs() < 1e-12);
+        assert!(changes[3].is_nan());
+        assert_eq!(changes[4], 0.0);
+        assert!(calc_multi_horizon_change_impl(&[], &[1])[0].is_nan());
+    }
+
+    // -----------------------------------------------------------------------
+    // Bollinger Bands
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn bollinger_bands_basic() {
+        let data = sample_prices();
+        let bands = calc_bollinger_impl(&data, 3, 2.0);
+        assert_eq!(bands.middle, calc_sma_impl(&data, 3));
+        assert_eq!(bands.upper.len(), 8);
+        // Closes 11, 12, 13: population stddev sqrt(2/3).
+        let offset = 2.0 * (2.0f64 / 3.0).sqrt();
+        assert!((bands.upper[0].value - (12.0 + offset)).abs() < 1e-12);
+        assert!((bands.lower[0].value - (12.0 - offset)).abs() < 1e-12);
+        assert_eq!(
+            calc_bollinger_impl(&data, 0, 2.0),
+            BollingerResult::default()
+        );
+    }
+
+    #[test]
+    fn bollinger_pctb_at_middle_is_half() {
+        // Closes 10, 12, 11: the last close sits on the SMA of 11.
+        let data: Vec<PricePoint> = [10.0, 12.0, 11.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| pp(i as f64, c, c, c, c, 1.0))
+            .collect();
+        let pctb = calc_bollinger_pctb_impl(&data, 3, 2.0);
+        assert_eq!(pctb.len(), 1);
+        assert!((pctb[0].value - 0.5).abs() < 1e-12);
+        assert_eq!(pctb[0].ts, 2.0);
+
+        let flat: Vec<PricePoint> = (0..4)
+            .map(|i| pp(i as f64, 5.0, 5.0, 5.0, 5.0, 1.0))
+            .collect();
+        let flat_pctb = calc_bollinger_pctb_impl(&flat, 3, 2.0);
+        assert_eq!(flat_pctb.len(), 2);
+        assert_eq!(flat_pctb[0].ts, 2.0);
+        assert!(flat_pctb.iter().all(|p| p.value.is_nan()));
+    }
+
+    #[test]
+    fn bollinger_bandwidth() {
+        let data = sample_prices();
+        let width = calc_bollinger_bandwidth_impl(&data, 3, 2.0);
+        let offset = 2.0 * (2.0f64 / 3.0).sqrt();
+        assert!((width[0].value - 2.0 * offset / 12.0).abs() < 1e-12);
+
+        let zero: Vec<PricePoint> = (0..4)
+            .map(|i| pp(i as f64, 0.0, 0.0, 0.0, 0.0, 1.0))
+            .collect();
+        let zero_width = calc_bollinger_bandwidth_impl(&zero, 3, 2.0);
+        assert_eq!(zero_width.len(), 2);
+        assert_eq!(zero_width[0].ts, 2.0);
+        assert!(zero_width.iter().all(|p| p.value.is_nan()));
+    }
+
+    // -----------------------------------------------------------------------
+    // ATR / Keltner / squeeze
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn atr_wilder_smoothing() {
+        let data = vec![
+            pp(1.0, 0.0, 12.0, 10.0, 11.0, 0.0),
+            pp(2.0, 0.0, 13.0, 11.0, 12.0, 0.0),
+            pp(3.0, 0.0, 16.0, 13.0, 15.0, 0.0),
+        ];
+        // True ranges: 2, 2, max(3, 4, 1) = 4.
+        let atr = calc_atr_impl(&data, 2);
+        assert_eq!(atr.len(), 2);
+        assert_eq!(
+            atr[0],
+            IndicatorPoint {
+                ts: 2.0,
+                value: 2.0
+            }
+        );
+        assert_eq!(atr[1].value, 3.0);
+        assert!(calc_atr_impl(&data, 0).is_empty());
+        assert!(calc_atr_impl(&data, 4).is_empty());
+    }
+
+    #[test]
+    fn keltner_channels_alignment() {
+        let data = sample_prices();
+        let kc = calc_keltner_impl(&data, 3, 5, 1.5);
+        assert_eq!(kc.middle.len(), 6);
+        assert_eq!(kc.middle[0].ts, data[4].ts);
+        let ema = calc_ema_impl(&data, 3);
+        let atr = calc_atr_impl(&data, 5);
+        assert_eq!(kc.middle[0].value, ema[2].value);
+        assert!((kc.upper[0].value - (ema[2].value + 1.5 * atr[0].value)).abs() < 1e-12);
+        assert_eq!(
+            calc_keltner_impl(&data, 3, 11, 1.5),
+            KeltnerResult::default()
+        );
+    }
+
+    #[test]
+    fn squeeze_on_when_bands_contract() {
+        // Wide bars with a nearly flat close: tiny Bollinger, wide Keltner.
+        let mut data: Vec<PricePoint> = (0..30)
+            .map(|i| {
+                let c = 100.0 + (i % 2) as f64 * 0.1;
+                pp(i as f64, c, c + 5.0, c - 5.0, c, 1000.0)
+            })
+            .collect();
+        let flags = detect_squeeze_impl(&data, 20, 2.0, 20, 10, 0.5);
+        assert_eq!(flags.len(), 11);
+        assert!(flags.iter().all(|&on| on));
+
+        // A large close move blows the Bollinger Bands out of the channel.
+        data[29] = pp(29.0, 100.0, 131.0, 129.0, 130.0, 1000.0);
+        let flags = detect_squeeze_impl(&data, 20, 2.0, 20, 10, 0.5);
+        assert!(!flags[10]);
+        assert!(detect_squeeze_impl(&data, 31, 2.0, 20, 10, 0.5).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Word-boundary name matching
+    // -----------------------------------------------------------------------
+
+    fn named(symbol: &str, name: &str) -> SymbolEntry {
+        SymbolEntry {
+            symbol: symbol.to_string(),
+            name: name.to_string(),
+            rank: None,
+        }
+    }
+
+    #[test]
+    fn word_boundary_matches_later_word() {
+        let entries = sample_entries();
+        let result = filter_symbols_mode_impl(&entries, "platforms", 10, MatchMode::WordBoundary);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].symbol, "META");
+    }
+
+    #[test]
+    fn word_boundary_drops_mid_word_and_promotes_word_start() {
+        let entries = vec![
+            named("SMRT", "Smart Co"),
+            named("ART", "Art Holdings"),
+            named("XYZ", "Big Art Group"),
+            named("ZZZ", "Alpha-Artisan"),
+        ];
+        let anywhere = filter_symbols_mode_impl(&entries, "art", 10, MatchMode::Anywhere);
+        assert_eq!(anywhere.len(), 4);
+        let bounded = filter_symbols_mode_impl(&entries, "art", 10, MatchMode::WordBoundary);
+        let symbols: Vec<&str> = bounded.iter().map(|e| e.symbol.as_str()).collect();
+        // Exact symbol first, then the two word-start names tied at 40.
+        assert_eq!(symbols, vec!["ART", "XYZ", "ZZZ"]);
+        assert_eq!(
+            filter_symbols_mode_impl(&entries, "art", 10, MatchMode::Anywhere),
+            filter_symbols_impl(&entries, "art", 10)
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Rank tie-breaking
+    // -----------------------------------------------------------------------
+
+    fn ranked(symbol: &str, rank: Option<u32>) -> SymbolEntry {
+        SymbolEntry {
+            rank,
+            ..named(symbol, "Holdings")
+        }
+    }
+
+    #[test]
+    fn rank_breaks_score_ties_before_symbol() {
+        let entries = vec![
+            ranked("ABC", None),
+            ranked("AAPL", Some(1)),
+            ranked("ABBV", Some(40)),
+            ranked("AAA", None),
+            ranked("A", None),
+        ];
+        let result = filter_symbols_impl(&entries, "a", 10);
+        let symbols: Vec<&str> = result.iter().map(|e| e.symbol.as_str()).collect();
+        // Exact match still wins; then ranked by popularity; unranked last.
+        assert_eq!(symbols, vec!["A", "AAPL", "ABBV", "AAA", "ABC"]);
+        assert_eq!(result[1].rank, Some(1));
+    }
+
+    // -----------------------------------------------------------------------
+    // Streaming VWAP
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn vwap_state_matches_batch() {
+        let data = vec![
+            pp(1.0, 0.0, 30.0, 10.0, 20.0, 100.0),
+            pp(2.0, 0.0, 60.0, 20.0, 40.0, 200.0),
+        ];
+        let mut state = VwapState::default();
+        assert_eq!(state.value(), 0.0);
+        let streamed: Vec<f64> = data.iter().map(|p| state.push_bar(p)).collect();
+        let batch: Vec<f64> = calc_vwap_impl(&data).iter().map(|p| p.value).collect();
+        assert_eq!(streamed, batch);
+        assert_eq!(state.value(), 10000.0 / 300.0);
+
+        let sample = sample_prices();
+        let mut state = VwapState::default();
+        let last = sample.iter().map(|p| state.push_bar(p)).last().unwrap();
+        assert_eq!(last, calc_vwap_impl(&sample).last().unwrap().value);
+    }
+
+    #[test]
+    fn vwap_state_reset_starts_new_session() {
+        let data = sample_prices();
+        let mut state = VwapState::default();
+        for p in &data {
+            state.push_bar(p);
+        }
+        state.reset();
+        assert_eq!(state.value(), 0.0);
+        assert_eq!(
+            state.push_bar(&data[3]),
+            calc_vwap_impl(&data[3..4])[0].value
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // max_results guards
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn filter_zero_and_oversized_max_results() {
+        let entries = sample_entries();
+        assert!(filter_symbols_impl(&entries, "a", 0).is_empty());
+        assert!(filter_symbols_impl(&entries, "", 0).is_empty());
+        let all = filter_symbols_impl(&entries, "", usize::MAX);
+        assert_eq!(all, entries);
+        let (hits, total) = filter_symbols_counted_impl(&entries, "a", usize::MAX);
+        assert_eq!(hits.len(), total);
+    }
+
+    #[test]
+    fn clamp_max_results_from_js() {
+        assert_eq!(clamp_max_results(-1.0, 7), 0);
+        assert_eq!(clamp_max_results(f64::NAN, 7), 0);
+        assert_eq!(clamp_max_results(0.0, 7), 0);
+        assert_eq!(clamp_max_results(3.9, 7), 3);
+        assert_eq!(clamp_max_results(4294967295.0, 7), 7);
+        assert_eq!(clamp_max_results(f64::INFINITY, 7), 7);
+    }
+
+    // -----------------------------------------------------------------------
+    // Price series
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn price_series_maps_source() {
+        let data = vec![pp(1.0, 10.0, 30.0, 10.0, 20.0, 100.0)];
+        let typical = calc_price_series_impl(&data, PriceSource::Typical);
+        assert_eq!(
+            typical,
+            vec![IndicatorPoint {
+                ts: 1.0,
+                value: 20.0
+            }]
+        );
+        // The single-bar VWAP is exactly the typical price.
+        assert_eq!(typical[0].value, calc_vwap_impl(&data)[0].value);
+        assert_eq!(
+            calc_price_series_impl(&data, PriceSource::Median)[0].value,
+            20.0
+        );
+        assert_eq!(
+            calc_price_series_impl(&data, PriceSource::WeightedClose)[0].value,
+            20.0
+        );
+        let closes = calc_price_series_impl(&sample_prices(), PriceSource::Close);
+        assert_eq!(closes.len(), 10);
+        assert_eq!(closes[9].value, 10.0);
+    }
+
+    // -----------------------------------------------------------------------
+    // Log space
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn log_space_round_trip() {
+        let points = vec![ip(1.0, 1.0), ip(2.0, 10.0), ip(3.0, 250.5)];
+        let logs = to_log_space_impl(&points, 10.0);
+        assert_eq!(logs[0].value, 0.0);
+        assert!((logs[1].value - 1.0).abs() < 1e-12);
+        let back = from_log_space_impl(&logs, 10.0);
+        for (b, p) in back.iter().zip(&points) {
+            assert_eq!(b.ts, p.ts);
+            assert!((b.value - p.value).abs() < 1e-9 * p.value);
+        }
+    }
+
+    #[test]
+    fn log_space_guards() {
+        let logs = to_log_space_impl(&[ip(1.0, 0.0), ip(2.0, -5.0)], 10.0);
+        assert!(logs.iter().all(|p| (p.value - (-12.0)).abs() < 1e-9));
+        let natural = to_log_space_impl(&[ip(1.0, std::f64::consts::E)], 1.0);
+        assert!((natural[0].value - 1.0).abs() < 1e-12);
+    }
+
+    // -----------------------------------------------------------------------
+    // LTTB indices
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn lttb_indices_increase_and_hit_bounds() {
+        let data: Vec<DataPoint> = (0..200)
+            .map(|i| dp(i as f64, ((i * 13) % 29) as f64))
+            .collect();
+        let indices = lttb_downsample_indices_impl(&data, 25);
+        assert_eq!(indices.len(), 25);
+        assert_eq!(indices[0], 0);
+        assert_eq!(*indices.last().unwrap(), 199);
+        assert!(indices.windows(2).all(|w| w[0] < w[1]));
+
+        let points: Vec<DataPoint> = indices.iter().map(|&i| data[i].clone()).collect();
+        assert_eq!(points, lttb_downsample_impl(&data, 25));
+        assert!(lttb_downsample_indices_impl(&[], 25).is_empty());
+    }
+
+    #[test]
+    fn lttb_near_boundary_thresholds() {
+        let data: Vec<DataPoint> = (0..10)
+            .map(|i| dp(i as f64, ((i * 7) % 5) as f64))
+            .collect();
+        for threshold in [8, 9] {
+            let indices = lttb_downsample_indices_impl(&data, threshold);
+            assert_eq!(indices.len(), threshold);
+            assert_eq!(indices[0], 0);
+            assert_eq!(indices[threshold - 1], 9);
+            // Strictly increasing: no repeated points.
+            assert!(indices.windows(2).all(|w| w[0] < w[1]));
+            // Each interior pick comes from its own bucket, so none skipped.
+            for (i, idx) in indices[1..threshold - 1].iter().enumerate() {
+                assert!(*idx >= lttb_bucket_start(i, 10, threshold));
+                assert!(*idx < lttb_bucket_start(i + 1, 10, threshold));
+            }
+        }
+        assert_eq!(lttb_downsample_indices_impl(&data, 11).len(), 10);
+    }
+
+    #[test]
+    fn lttb_bucket_bounds_are_exact() {
+        // (len - 2) / (threshold - 2) is not representable in binary here,
+        // but integer bounds still tile the interior exactly.
+        let (len, threshold) = (1_000_003, 49_999);
+        assert_eq!(lttb_bucket_start(0, len, threshold), 1);
+        assert_eq!(lttb_bucket_start(threshold - 2, len, threshold), len - 1);
+        assert!((0..threshold - 2).all(|i| {
+            lttb_bucket_start(i + 1, len, threshold) > lttb_bucket_start(i, len, threshold)
+        }));
+    }
+
+    #[test]
+    fn lttb_bucket_start_survives_products_above_u32() {
+        // i * (len - 2) runs past u32::MAX here, which would wrap a 32-bit
+        // usize; compare every boundary against a u128 reference.
+        let (len, threshold) = (1_000_003usize, 49_999usize);
+        assert!((threshold - 2) as u64 * (len - 2) as u64 > u32::MAX as u64);
+        for i in 0..=threshold - 2 {
+            let expected = 1 + i as u128 * (len - 2) as u128 / (threshold - 2) as u128;
+            assert_eq!(lttb_bucket_start(i, len, threshold) as u128, expected);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Position sizing
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn position_size_from_stop() {
+        // Risk 1% of 100k = 1000 over a 2.50 stop distance.
+        assert_eq!(position_size_impl(100_000.0, 0.01, 50.0, 47.5), 400.0);
+        assert_eq!(position_size_impl(100_000.0, 0.01, 47.5, 50.0), 400.0);
+        assert_eq!(position_size_impl(100_000.0, 0.01, 50.0, 50.0), 0.0);
+    }
+
+    #[test]
+    fn position_size_from_atr() {
+        assert_eq!(position_size_atr_impl(50_000.0, 0.02, 2.0, 2.5), 200.0);
+        assert_eq!(position_size_atr_impl(50_000.0, 0.02, 0.0, 2.5), 0.0);
+        assert_eq!(position_size_atr_impl(50_000.0, 0.02, f64::NAN, 2.5), 0.0);
+    }
+
+    // -----------------------------------------------------------------------
+    // Crossover backtest
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn backtest_cross_trades_zigzag() {
+        let data = zigzag_prices();
+        let events = detect_ma_cross_impl(&data, 2, 5, MaKind::Sma);
+        let result = backtest_cross_impl(&data, 2, 5, MaKind::Sma);
+        assert_eq!(result.equity.len(), data.len());
+        assert_eq!(result.equity[0].value, 1.0);
+        assert_eq!(result.trades, 1);
+
+        // Filled at the open after each signal (open == close here).
+        let entry = data[events[0].index + 1].open;
+        let exit = data[events[1].index + 1].open;
+        assert!((result.total_return - (exit / entry - 1.0)).abs() < 1e-12);
+        assert_eq!(result.win_rate, if exit > entry { 1.0 } else { 0.0 });
+        // Flat before the entry fill.
+        assert!(result.equity[..=events[0].index]
+            .iter()
+            .all(|e| e.value == 1.0));
+    }
+
+    #[test]
+    fn backtest_cross_open_position_and_guards() {
+        // Fall then rise: a golden cross with no exit, marked at the last close.
+        let data: Vec<PricePoint> = zigzag_prices().into_iter().take(20).collect();
+        let result = backtest_cross_impl(&data, 2, 5, MaKind::Sma);
+        assert_eq!(result.trades, 1);
+        assert_eq!(result.win_rate, 1.0);
+        assert!(result.total_return > 0.0);
+
+        assert_eq!(
+            backtest_cross_impl(&data, 2, 50, MaKind::Sma),
+            BacktestResult::default()
+        );
+        assert_eq!(
+            backtest_cross_impl(&data, 0, 5, MaKind::Ema),
+            BacktestResult::default()
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Drawdown / signal backtest
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn max_drawdown_peak_to_trough() {
+        assert_eq!(
+            max_drawdown_over_values(&[100.0, 120.0, 90.0, 130.0, 117.0]),
+            0.25
+        );
+        assert_eq!(max_drawdown_over_values(&[1.0, 2.0, 3.0]), 0.0);
+        assert_eq!(max_drawdown_over_values(&[]), 0.0);
+        // sample_prices peaks at 15 and ends at 10.
+        assert!((calc_drawdown_impl(&sample_prices()) - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn backtest_signals_always_long_is_buy_and_hold() {
+        let data = sample_prices();
+        let result = backtest_signals_impl(&data, &[1; 10]);
+        assert_eq!(result.equity.len(), data.len());
+        assert_eq!(result.equity[0].value, 1.0);
+        let buy_and_hold = data[9].close / data[0].close - 1.0;
+        assert!((result.total_return - buy_and_hold).abs() < 1e-12);
+        for (e, p) in result.equity.iter().zip(&data) {
+            assert!((e.value - p.close / data[0].close).abs() < 1e-12);
+        }
+        assert!((result.max_drawdown - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(result.trades, 1);
+        assert_eq!(result.win_rate, 0.0);
+    }
+
+    #[test]
+    fn backtest_signals_short_and_flat() {
+        let data = sample_prices();
+        // Long over the rise (bars 0..4), short over the fall, flat at the end.
+        let signals = [1, 1, 1, 1, -1, -1, -1, -1, 0];
+        let result = backtest_signals_impl(&data, &signals);
+        let long = 15.0 / 11.0;
+        let short = (1.0 - (14.0 / 15.0 - 1.0)) * (1.0 - (13.0 / 14.0 - 1.0));
+        let short = short * (1.0 - (12.0 / 13.0 - 1.0)) * (1.0 - (11.0 / 12.0 - 1.0));
+        assert!((result.total_return - (long * short - 1.0)).abs() < 1e-12);
+        assert_eq!(result.trades, 3);
+        assert_eq!(result.win_rate, 1.0);
+        assert_eq!(backtest_signals_impl(&[], &[1]), BacktestResult::default());
+        assert_eq!(backtest_signals_impl(&data, &[]).total_return, 0.0);
+    }
+
+    // -----------------------------------------------------------------------
+    // Returns / Sharpe / Sortino
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn returns_close_to_close() {
+        let data = sample_prices();
+        let returns = calc_returns_impl(&data);
+        assert_eq!(returns.len(), 9);
+        assert_eq!(returns[0].ts, data[1].ts);
+        assert!((returns[0].value - (12.0 / 11.0 - 1.0)).abs() < 1e-12);
+        assert!(calc_returns_impl(&data[..1]).is_empty());
+    }
+
+    #[test]
+    fn sharpe_and_sortino_constant_returns_are_zero() {
+        let returns = [0.01; 12];
+        assert_eq!(calc_sharpe_impl(&returns, 0.0, 252.0), 0.0);
+        assert_eq!(calc_sortino_impl(&returns, 0.0, 252.0), 0.0);
+        assert_eq!(calc_sharpe_impl(&[0.01], 0.0, 252.0), 0.0);
+        assert_eq!(calc_sortino_impl(&[], 0.0, 252.0), 0.0);
+    }
+
+    #[test]
+    fn sharpe_and_sortino_two_points() {
+        // Excess returns 0.02 and -0.01: mean 0.005, sample stddev
+        // 0.03 / sqrt(2), downside deviation sqrt(0.0001 / 2).
+        let returns = [0.03, 0.0];
+        let sharpe = calc_sharpe_impl(&returns, 0.01, 4.0);
+        assert!((sharpe - 0.005 / (0.03 / 2f64.sqrt()) * 2.0).abs() < 1e-12);
+        let sortino = calc_sortino_impl(&returns, 0.01, 4.0);
+        assert!((sortino - 0.005 / (0.0001f64 / 2.0).sqrt() * 2.0).abs() < 1e-12);
+    }
+
+    // -----------------------------------------------------------------------
+    // Summary stats
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn summary_stats_basic() {
+        let data = sample_prices();
+        let stats = summary_stats_impl(&data, 9.0);
+        assert!((stats.total_return - (10.0 / 11.0 - 1.0)).abs() < 1e-12);
+        // Nine intervals at nine per year: CAGR equals the total return.
+        assert!((stats.cagr - stats.total_return).abs() < 1e-12);
+        assert!((stats.max_drawdown - 1.0 / 3.0).abs() < 1e-12);
+        assert!(stats.volatility > 0.0);
+
+        let yearly = summary_stats_impl(&data, 18.0);
+        assert!((yearly.cagr - ((10.0f64 / 11.0).powi(2) - 1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn summary_stats_volatility_and_guards() {
+        let returns = [0.01, 0.03];
+        assert!((annualized_volatility(&returns, 4.0) - 2.0 * 0.0002f64.sqrt()).abs() < 1e-12);
+        assert_eq!(summary_stats_impl(&[], 252.0), SummaryStats::default());
+        assert_eq!(
+            summary_stats_impl(&sample_prices()[..1], 252.0),
+            SummaryStats::default()
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Parabolic SAR
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn psar_trails_an_uptrend() {
+        let data = rising_prices(20);
+        let sar = calc_psar_impl(&data, 0.02, 0.2);
+        assert_eq!(sar.len(), 19);
+        assert_eq!(
+            sar[0],
+            IndicatorPoint {
+                ts: data[1].ts,
+                value: data[0].low
+            }
+        );
+        assert!(sar.iter().zip(&data[1..]).all(|(s, p)| s.value < p.low));
+        assert!(sar.windows(2).all(|w| w[1].value >= w[0].value));
+        assert!(psar_signals_impl(&data, 0.02, 0.2).is_empty());
+        assert!(calc_psar_impl(&data[..1], 0.02, 0.2).is_empty());
+    }
+
+    #[test]
+    fn psar_signals_on_flips() {
+        let data = zigzag_prices();
+        let events = psar_signals_impl(&data, 0.02, 0.2);
+        let kinds: Vec<SignalKind> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![SignalKind::PsarBuy, SignalKind::PsarSell]);
+        let buy = &events[0];
+        assert!(buy.index > 10 && buy.index < 20);
+        assert_eq!(buy.ts, data[buy.index].ts);
+        let sar = calc_psar_impl(&data, 0.02, 0.2)[buy.index - 1].value;
+        assert!(sar < data[buy.index].low);
+        assert!((buy.strength - relative_gap(sar, data[buy.index].close)).abs() < 1e-12);
+    }
+
+    // -----------------------------------------------------------------------
+    // Signal merging
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn merge_signals_chronological() {
+        let data = zigzag_prices();
+        let crosses = detect_ma_cross_impl(&data, 2, 5, MaKind::Sma);
+        let flips = psar_signals_impl(&data, 0.02, 0.2);
+        let merged = merge_signals_impl(&[crosses.clone(), flips.clone()]);
+        assert_eq!(merged.len(), crosses.len() + flips.len());
+        assert!(merged.windows(2).all(|w| w[0].ts <= w[1].ts));
+        assert!(crosses.iter().chain(&flips).all(|s| merged.contains(s)));
+        assert!(merge_signals_impl(&[]).is_empty());
+    }
+
+    #[test]
+    fn merge_signals_ties_keep_stream_order() {
+        let signal = |ts: f64, kind: SignalKind| Signal {
+            ts,
+            index: ts as usize,
+            kind,
+            strength: 0.0,
+        };
+        let a = vec![
+            signal(1.0, SignalKind::GoldenCross),
+            signal(3.0, SignalKind::DeathCross),
+        ];
+        let b = vec![
+            signal(1.0, SignalKind::PsarBuy),
+            signal(2.0, SignalKind::PsarSell),
+        ];
+        let kinds: Vec<SignalKind> = merge_signals_impl(&[a, b]).iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SignalKind::GoldenCross,
+                SignalKind::PsarBuy,
+                SignalKind::PsarSell,
+                SignalKind::DeathCross,
+            ]
+        );
+        assert!(SignalKind::PsarBuy.is_bullish());
+        assert!(!SignalKind::DeathCross.is_bullish());
+    }
+
+    // -----------------------------------------------------------------------
+    // ATR trailing stop
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn atr_stop_rises_in_uptrend() {
+        let data = rising_prices(30);
+        let stop = calc_atr_stop_impl(&data, 5, 2.0);
+        assert_eq!(stop.len(), 26);
+        assert_eq!(stop[0].ts, data[4].ts);
+        let atr = calc_atr_impl(&data, 5);
+        assert!((stop[0].value - (data[4].close - 2.0 * atr[0].value)).abs() < 1e-12);
+        assert!(stop.windows(2).all(|w| w[1].value >= w[0].value));
+        assert!(stop.iter().zip(&data[4..]).all(|(s, p)| s.value < p.close));
+    }
+
+    #[test]
+    fn atr_stop_monotonic_within_each_trend() {
+        let data = zigzag_prices();
+        let stop = calc_atr_stop_impl(&data, 3, 1.5);
+        let bars = &data[2..];
+        let uptrend: Vec<bool> = stop
+            .iter()
+            .zip(bars)
+            .map(|(s, p)| s.value <= p.close)
+            .collect();
+        assert!(uptrend.contains(&false) && uptrend.contains(&true));
+        for i in 1..stop.len() {
+            if uptrend[i] == uptrend[i - 1] {
+                if uptrend[i] {
+                    assert!(stop[i].value >= stop[i - 1].value);
+                } else {
+                    assert!(stop[i].value <= stop[i - 1].value);
+                }
+            }
+        }
+        assert!(calc_atr_stop_impl(&data, 0, 1.0).is_empty());
+        assert!(calc_atr_stop_impl(&data, 31, 1.0).is_empty());
+    }
+
+    #[test]
+    fn atr_stop_empty_or_short_data_is_empty() {
+        assert!(calc_atr_stop_impl(&[], 14, 2.0).is_empty());
+        let data = rising_prices(5);
+        // Periods that would slice past the end of `data`.
+        assert!(calc_atr_stop_impl(&data, 7, 2.0).is_empty());
+        assert!(calc_atr_stop_impl(&data, usize::MAX, 2.0).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Cutler's RSI
+    // -----------------------------------------------------------------------
+
+    /// Cutler's RSI computed naively: a fresh window for every output.
+    fn cutler_reference(data: &[PricePoint], period: usize) -> Vec<IndicatorPoint> {
+        (period..data.len())
+            .map(|end| {
+                let window = &data[end - period..=end];
+                let (mut gain, mut loss) = (0.0, 0.0);
+                for w in window.windows(2) {
+                    let change = w[1].close - w[0].close;
+                    if change > 0.0 {
+                        gain += change;
+                    } else {
+                        loss -= change;
+                    }
+                }
+                IndicatorPoint {
+                    ts: data[end].ts,
+                    value: rsi_from_averages(gain / period as f64, loss / period as f64),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rsi_sma_matches_cutler_reference() {
+        let data = sample_prices();
+        let cutler = calc_rsi_smoothed_impl(&data, 3, SmoothingMethod::Sma);
+        let reference = cutler_reference(&data, 3);
+        assert_eq!(cutler.len(), data.len() - 3);
+        assert_eq!(cutler[0].ts, data[3].ts);
+        for (c, r) in cutler.iter().zip(&reference) {
+            assert_eq!(c.ts, r.ts);
+            assert!((c.value - r.value).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn rsi_wilder_and_cutler_diverge_after_seed() {
+        let data = sample_prices();
+        let wilder = calc_rsi_smoothed_impl(&data, 3, SmoothingMethod::Wilder);
+        let cutler = calc_rsi_smoothed_impl(&data, 3, SmoothingMethod::Sma);
+        assert_eq!(wilder.len(), cutler.len());
+        assert_eq!(wilder[0], cutler[0]);
+        // Three straight losses after the peak: Cutler's window forgets the
+        // gains entirely and hits 0; Wilder's smoothing still remembers them.
+        assert_eq!(cutler[4].value, 0.0);
+        assert!(wilder[4].value > 0.0);
+        assert!(wilder
+            .iter()
+            .zip(&cutler)
+            .skip(1)
+            .any(|(w, c)| (w.value - c.value).abs() > 1.0));
+    }
+
+    #[test]
+    fn rsi_sma_running_sums_track_reference_on_long_series() {
+        // Fractional, noisy changes followed by a strictly falling tail: the
+        // sliding sums must match a fresh re-sum and land exactly on 0.
+        let mut data: Vec<PricePoint> = (0..300)
+            .map(|i| {
+                let c = 100.0 + (i as f64 * 0.7).sin() * 3.3 + i as f64 * 0.01;
+                pp(i as f64, c, c, c, c, 1.0)
+            })
+            .collect();
+        let last = data.last().unwrap().close;
+        data.extend((1..=10).map(|i| {
+            let c = last - i as f64 * 0.1;
+            pp(300.0 + i as f64, c, c, c, c, 1.0)
+        }));
+        let cutler = calc_rsi_smoothed_impl(&data, 5, SmoothingMethod::Sma);
+        let reference = cutler_reference(&data, 5);
+        assert_eq!(cutler.len(), reference.len());
+        for (c, r) in cutler.iter().zip(&reference) {
+            assert!((c.value - r.value).abs() < 1e-9);
+        }
+        assert!(cutler.iter().rev().take(5).all(|p| p.value == 0.0));
+    }
+
+    // -----------------------------------------------------------------------
+    // Warm-up bars
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn warmup_bars_match_indicator_output() {
+        let data = rising_prices(40);
+        let n = data.len();
+        let period = 7;
+        let cases: Vec<(&str, usize)> = vec![
+            ("sma", calc_sma_impl(&data, period).len()),
+            ("ema", calc_ema_impl(&data, period).len()),
+            ("rsi", calc_rsi_impl(&data, period).len()),
+            ("vwap", calc_vwap_impl(&data).len()),
+            ("atr", calc_atr_impl(&data, period).len()),
+            (
+                "bollinger",
+                calc_bollinger_impl(&data, period, 2.0).middle.len(),
+            ),
+            (
+                "keltner",
+                calc_keltner_impl(&data, period, period, 2.0).middle.len(),
+            ),
+            ("rolling_vwap", calc_rolling_vwap_impl(&data, period).len()),
+            ("psar", calc_psar_impl(&data, 0.02, 0.2).len()),
+            ("returns", calc_returns_impl(&data).len()),
+        ];
+        for (name, len) in cases {
+            assert_eq!(warmup_bars_impl(name, period), Some(n - len), "{}", name);
+        }
+        assert_eq!(warmup_bars_impl("RSI", 14), Some(14));
+        assert_eq!(warmup_bars_impl("nope", 14), None);
+    }
+
+    #[test]
+    fn warmup_bars_cover_every_indicator_spec_kind() {
+        let data = rising_prices(40);
+        for period in [1, 5, 14] {
+            for kind in ["sma", "ema", "rsi", "vwap"] {
+                let out = compute_indicator_impl(&data, &spec(kind, period));
+                assert!(!out.is_empty(), "{}", kind);
+                assert_eq!(
+                    warmup_bars_impl(kind, period),
+                    Some(data.len() - out.len()),
+                    "{}_{}",
+                    kind,
+                    period
+                );
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Multi-universe search
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn multi_universe_search_merges_by_score_and_tags_source() {
+        let universes = vec![
+            (
+                "equities".to_string(),
+                vec![named("BTCS", "BTCS Inc"), named("AAPL", "Apple Inc")],
+            ),
+            (
+                "crypto".to_string(),
+                vec![named("WBTC", "Wrapped Bitcoin"), named("BTC", "Bitcoin")],
+            ),
+        ];
+
+        let hits = filter_symbols_multi_impl(&universes, "btc", 10);
+        let got: Vec<(&str, &str)> = hits
+            .iter()
+            .map(|h| (h.symbol.as_str(), h.source.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![("BTC", "crypto"), ("BTCS", "equities"), ("WBTC", "crypto")]
+        );
+
+        // Truncation is global, not per universe.
+        let top = filter_symbols_multi_impl(&universes, "btc", 2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[1].source, "equities");
+
+        // An empty query pages through the universes in order.
+        let page = filter_symbols_multi_impl(&universes, "", 3);
+        let syms: Vec<&str> = page.iter().map(|h| h.symbol.as_str()).collect();
+        assert_eq!(syms, vec!["BTCS", "AAPL", "WBTC"]);
+    }
+
+    // -----------------------------------------------------------------------
+    // Relative volume
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn rvol_flags_volume_spike() {
+        let mut data = rising_prices(6);
+        data[5].volume = 4000.0;
+        let rvol = calc_rvol_impl(&data, 3);
+        assert_eq!(rvol.len(), 4);
+        assert_eq!(rvol[0].ts, data[2].ts);
+        assert!((rvol[0].value - 1.0).abs() < 1e-12);
+        // 4000 / mean(1000, 1000, 4000) = 2.
+        assert!((rvol[3].value - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rvol_zero_volume_window_and_short_input() {
+        let mut data = rising_prices(3);
+        for p in &mut data {
+            p.volume = 0.0;
+        }
+        assert_eq!(calc_rvol_impl(&data, 3)[0].value, 0.0);
+        assert!(calc_rvol_impl(&data, 4).is_empty());
+        assert!(calc_rvol_impl(&data, 0).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Cumulative and rolling returns
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn cumulative_return_of_doubling_price_is_one() {
+        let data: Vec<PricePoint> = [50.0, 60.0, 80.0, 100.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| pp(i as f64, c, c, c, c, 1.0))
+            .collect();
+        let cum = calc_cumulative_return_impl(&data);
+        assert_eq!(cum.len(), 4);
+        assert_eq!(cum[0].value, 0.0);
+        assert!((cum[3].value - 1.0).abs() < 1e-12);
+
+        let rolling = calc_rolling_return_impl(&data, 2);
+        assert_eq!(rolling.len(), 2);
+        assert_eq!(rolling[0].ts, 2.0);
+        assert!((rolling[0].value - 0.6).abs() < 1e-12);
+        assert!((rolling[1].value - 100.0 / 60.0 + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn cumulative_and_rolling_return_guard_zero_base() {
+        let data = vec![
+            pp(0.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+            pp(1.0, 5.0, 5.0, 5.0, 5.0, 1.0),
+            pp(2.0, 10.0, 10.0, 10.0, 10.0, 1.0),
+        ];
+        assert!(calc_cumulative_return_impl(&data).is_empty());
+        let rolling = calc_rolling_return_impl(&data, 1);
+        assert_eq!(rolling.len(), 1);
+        assert_eq!(rolling[0].ts, 2.0);
+        assert!(calc_rolling_return_impl(&data, 3).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Smoothed LTTB
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn smoothed_lttb_window_one_matches_plain() {
+        let data: Vec<DataPoint> = (0..50)
+            .map(|i| dp(i as f64, ((i * 7) % 11) as f64))
+            .collect();
+        assert_eq!(
+            lttb_downsample_smoothed_impl(&data, 10, 1),
+            lttb_downsample_impl(&data, 10)
+        );
+    }
+
+    #[test]
+    fn smoothed_lttb_returns_original_values() {
+        let data: Vec<DataPoint> = (0..60)
+            .map(|i| {
+                let noise = if i % 2 == 0 { 3.0 } else { -3.0 };
+                dp(i as f64, i as f64 + noise)
+            })
+            .collect();
+        let out = lttb_downsample_smoothed_impl(&data, 12, 5);
+        assert_eq!(out.len(), 12);
+        assert_eq!(out[0], data[0]);
+        assert_eq!(out[11], data[59]);
+        for p in &out {
+            assert_eq!(*p, data[p.ts as usize]);
+        }
+    }
+
+    #[test]
+    fn smoothed_lttb_breaks_area_ties_toward_earlier_index() {
+        // A period-3 pattern smooths (window 3) to 1.5, 0, 0, 0, 1.5, so
+        // points 1..=3 of the single bucket tie at area 6. Plain LTTB on the
+        // raw values picks the -3 spike at index 2 instead.
+        let data: Vec<DataPoint> = [0.0, 3.0, -3.0, 0.0, 3.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| dp(i as f64, v))
+            .collect();
+        assert_eq!(lttb_downsample_indices_impl(&data, 3), vec![0, 2, 4]);
+        let out = lttb_downsample_smoothed_impl(&data, 3, 3);
+        assert_eq!(out, vec![data[0].clone(), data[1].clone(), data[4].clone()]);
+
+        // Nudging the raw value at index 1 lets index 2 win by about 5e-13,
+        // which is rounding noise, so the earlier index still holds.
+        let mut nudged = data.clone();
+        nudged[1].value -= 1e-12;
+        let out = lttb_downsample_smoothed_impl(&nudged, 3, 3);
+        assert_eq!(out[1], nudged[1]);
+    }
+
+    // -----------------------------------------------------------------------
+    // Envelope
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn envelope_on_flat_series_is_five_percent() {
+        let data: Vec<PricePoint> = (0..10)
+            .map(|i| pp(i as f64, 100.0, 100.0, 100.0, 100.0, 1.0))
+            .collect();
+        for kind in [MaKind::Sma, MaKind::Ema] {
+            let env = calc_envelope_impl(&data, 4, 0.05, kind);
+            assert_eq!(env.middle.len(), 7);
+            assert_eq!(env.upper[0].ts, 3.0);
+            assert!(env.upper.iter().all(|p| (p.value - 105.0).abs() < 1e-9));
+            assert!(env.lower.iter().all(|p| (p.value - 95.0).abs() < 1e-9));
+        }
+        assert_eq!(
+            calc_envelope_impl(&data, 0, 0.05, MaKind::Sma),
+            EnvelopeResult::default()
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Price gaps
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn price_gap_detects_overnight_gap_up() {
+        let data = vec![
+            pp(0.0, 99.0, 101.0, 98.0, 100.0, 1.0),
+            pp(1.0, 105.0, 106.0, 104.0, 105.5, 1.0),
+            pp(2.0, 105.0, 106.0, 104.0, 105.0, 1.0),
+        ];
+        let gaps = detect_price_gaps_impl(&data, 0.02);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].ts, 1.0);
+        assert_eq!(gaps[0].kind, GapKind::GapUp);
+        assert!((gaps[0].pct - 0.05).abs() < 1e-12);
+
+        assert!(detect_price_gaps_impl(&data[..1], 0.02).is_empty());
+    }
+
+    #[test]
+    fn price_gap_detects_gap_down() {
+        let data = vec![
+            pp(0.0, 100.0, 100.0, 100.0, 100.0, 1.0),
+            pp(1.0, 90.0, 91.0, 89.0, 90.0, 1.0),
+        ];
+        let gaps = detect_price_gaps_impl(&data, 0.05);
+        assert_eq!(gaps[0].kind, GapKind::GapDown);
+        assert!((gaps[0].pct + 0.10).abs() < 1e-12);
+    }
+
+    // -----------------------------------------------------------------------
+    // Beta
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn rolling_beta_of_leveraged_series_is_two() {
+        let bench_returns = [0.01, -0.02, 0.03, 0.005, -0.01, 0.02];
+        let (mut b, mut a) = (100.0, 50.0);
+        let mut benchmark = vec![pp(0.0, b, b, b, b, 1.0)];
+        let mut asset = vec![pp(0.0, a, a, a, a, 1.0)];
+        for (i, r) in bench_returns.iter().enumerate() {
+            let ts = (i + 1) as f64;
+            b *= 1.0 + r;
+            a *= 1.0 + 2.0 * r;
+            benchmark.push(pp(ts, b, b, b, b, 1.0));
+            asset.push(pp(ts, a, a, a, a, 1.0));
+        }
+        // A bar only the asset has is dropped by the join.
+        asset.insert(3, pp(2.5, 1.0, 1.0, 1.0, 1.0, 1.0));
+
+        assert!((calc_beta_impl(&asset, &benchmark) - 2.0).abs() < 1e-9);
+        let rolling = calc_rolling_beta_impl(&asset, &benchmark, 3);
+        assert_eq!(rolling.len(), 4);
+        assert_eq!(rolling[0].ts, 3.0);
+        assert!(rolling.iter().all(|p| (p.value - 2.0).abs() < 1e-9));
+        assert!(calc_rolling_beta_impl(&asset, &benchmark, 7).is_empty());
+    }
+
+    #[test]
+    fn rolling_beta_flat_benchmark_is_zero() {
+        let benchmark: Vec<PricePoint> = (0..5)
+            .map(|i| pp(i as f64, 10.0, 10.0, 10.0, 10.0, 1.0))
+            .collect();
+        let asset = rising_prices(5);
+        let rolling = calc_rolling_beta_impl(&asset, &benchmark, 2);
+        assert_eq!(rolling.len(), 3);
+        assert!(rolling.iter().all(|p| p.value == 0.0));
+    }
+
+    // -----------------------------------------------------------------------
+    // Time-bucketed LTTB
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn time_lttb_skips_empty_slices() {
+        // 40 dense points in [0, 40), then a long gap, then 2 points near
+        // the end: most time slices are empty.
+        let mut data: Vec<DataPoint> = (0..40).map(|i| dp(i as f64, (i % 5) as f64)).collect();
+        data.push(dp(950.0, 3.0));
+        data.push(dp(1000.0, 1.0));
+
+        let out = lttb_downsample_time_impl(&data, 12);
+        assert!(out.len() < 12);
+        assert_eq!(out.first(), data.first());
+        assert_eq!(out.last(), data.last());
+        assert!(out.windows(2).all(|w| w[0].ts < w[1].ts));
+        // The dense prefix shares a single slice.
+        assert_eq!(out.iter().filter(|p| p.ts < 40.0).count(), 2);
+    }
+
+    #[test]
+    fn time_lttb_on_uniform_spacing_keeps_threshold() {
+        let data: Vec<DataPoint> = (0..100)
+            .map(|i| dp(i as f64, ((i * 13) % 17) as f64))
+            .collect();
+        let out = lttb_downsample_time_impl(&data, 10);
+        assert_eq!(out.len(), 10);
+        assert_eq!(
+            lttb_downsample_time_impl(&data[..5], 10),
+            data[..5].to_vec()
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Candle directions
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn candle_directions_mark_up_and_down_bars() {
+        let data = vec![
+            pp(0.0, 10.0, 12.0, 9.0, 11.0, 1.0),
+            pp(1.0, 11.0, 12.0, 9.0, 10.0, 1.0),
+            pp(2.0, 10.0, 10.0, 10.0, 10.0, 1.0),
+        ];
+        assert_eq!(candle_directions_impl(&data), vec![1, -1, 1]);
+        assert!(candle_directions_impl(&[]).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Streaming SMA
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn sma_state_matches_batch_sma() {
+        let data = sample_prices();
+        let batch = calc_sma_impl(&data, 3);
+        let mut state = SmaState::new(3);
+        let streamed: Vec<Option<f64>> = data.iter().map(|p| state.push(p.close)).collect();
+
+        assert_eq!(streamed[..2], [None, None]);
+        let values: Vec<f64> = streamed[2..].iter().map(|v| v.unwrap()).collect();
+        let expected: Vec<f64> = batch.iter().map(|p| p.value).collect();
+        assert_eq!(values, expected);
+        assert_eq!(state.value(), Some(expected[expected.len() - 1]));
+    }
+
+    #[test]
+    fn sma_state_zero_period_never_emits() {
+        let mut state = SmaState::new(0);
+        assert_eq!(state.push(1.0), None);
+        assert_eq!(state.value(), None);
+    }
+
+    #[test]
+    fn sma_state_huge_period_does_not_preallocate() {
+        let mut state = SmaState::new(usize::MAX);
+        assert_eq!(state.push(1.0), None);
+        assert_eq!(state.value(), None);
+    }
+
+    // -----------------------------------------------------------------------
+    // Coverage bonus
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn coverage_bonus_ranks_closer_prefix_first() {
+        let entries = vec![
+            named("AAPLX", "Apple Extended Fund"),
+            named("AAPL", "Apple Inc."),
+            named("XAAP", "Xaap Corp"),
+        ];
+        let result = filter_symbols_impl(&entries, "aap", 10);
+        let symbols: Vec<&str> = result.iter().map(|e| e.symbol.as_str()).collect();
+        // Both prefixes beat the full-coverage contains match on tier.
+        assert_eq!(symbols, vec!["AAPL", "AAPLX", "XAAP"]);
+        assert_eq!(coverage_bonus(3, 4), 15);
+        assert_eq!(coverage_bonus(4, 4), 19);
+        assert_eq!(coverage_bonus(3, 0), 0);
+    }
+
+    #[test]
+    fn coverage_bonus_orders_after_rank() {
+        let entries = vec![
+            ranked("AAPL", Some(1)),
+            ranked("ABC", None),
+            ranked("AAA", None),
+            ranked("AMZN", Some(2)),
+            ranked("AZ", None),
+        ];
+        let result = filter_symbols_impl(&entries, "a", 10);
+        let symbols: Vec<&str> = result.iter().map(|e| e.symbol.as_str()).collect();
+        // Ranked prefixes first despite their smaller bonus; the bonus only
+        // orders the unranked ones.
+        assert_eq!(symbols, vec!["AAPL", "AMZN", "AZ", "AAA", "ABC"]);
+    }
+
+    // -----------------------------------------------------------------------
+    // Columnar indicator output
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn indicator_columns_split_points() {
+        let sma = calc_sma_impl(&sample_prices(), 3);
+        let cols = to_indicator_columns_impl(&sma);
+        assert_eq!(cols.ts.len(), sma.len());
+        for (i, p) in sma.iter().enumerate() {
+            assert_eq!((cols.ts[i], cols.value[i]), (p.ts, p.value));
+        }
+        assert_eq!(to_indicator_columns_impl(&[]), IndicatorColumns::default());
+    }
+
+    // -----------------------------------------------------------------------
+    // Schaff Trend Cycle
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn stc_alignment_and_range() {
+        let data = zigzag_prices();
+        let stc = calc_stc_impl(&data, 3, 6, 3);
+        // First value at bar max(3, 6) + 2 * 3 - 3 = 9.
+        assert_eq!(stc.len(), data.len() - 9);
+        assert_eq!(stc[0].ts, data[9].ts);
+        assert!(stc.iter().all(|p| (0.0..=100.0).contains(&p.value)));
+        // Deep in the final decline the cycle bottoms out.
+        assert!(stc.last().unwrap().value < 5.0, "{:?}", stc.last());
+    }
+
+    #[test]
+    fn stc_flat_series_reads_fifty() {
+        let data: Vec<PricePoint> = (0..20)
+            .map(|i| pp(i as f64, 10.0, 10.0, 10.0, 10.0, 1.0))
+            .collect();
+        let stc = calc_stc_impl(&data, 3, 6, 3);
+        assert!(stc.iter().all(|p| p.value == 50.0));
+        assert!(calc_stc_impl(&data[..8], 3, 6, 3).is_empty());
+        assert!(calc_stc_impl(&data, 3, 6, 0).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Connors RSI
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn close_streaks_count_runs() {
+        let closes = [10.0, 11.0, 12.0, 12.0, 11.0, 10.0, 11.0];
+        let data: Vec<PricePoint> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| pp(i as f64, c, c, c, c, 1.0))
+            .collect();
+        assert_eq!(
+            close_streaks(&data),
+            vec![0.0, 1.0, 2.0, 0.0, -1.0, -2.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn connors_rsi_averages_its_components() {
+        let data = zigzag_prices();
+        let crsi = calc_connors_rsi_impl(&data, 3, 2, 5);
+        // All components defined from bar max(3, 2, 5 + 1) = 6.
+        assert_eq!(crsi.len(), data.len() - 6);
+        assert_eq!(crsi[0].ts, data[6].ts);
+
+        let i = 20;
+        let rsi = calc_rsi_impl(&data, 3)[i - 3].value;
+        let streaks: Vec<PricePoint> = data
+            .iter()
+            .zip(close_streaks(&data))
+            .map(|(p, s)| PricePoint {
+                close: s,
+                ..p.clone()
+            })
+            .collect();
+        let streak_rsi = calc_rsi_impl(&streaks, 2)[i - 2].value;
+        let below = (i - 5..i)
+            .filter(|&j| data[j].close / data[j - 1].close < data[i].close / data[i - 1].close)
+            .count();
+        let rank = 100.0 * below as f64 / 5.0;
+        assert!((crsi[i - 6].value - (rsi + streak_rsi + rank) / 3.0).abs() < 1e-9);
+        assert!(crsi.iter().all(|p| (0.0..=100.0).contains(&p.value)));
+    }
+
+    #[test]
+    fn connors_rsi_insufficient_data() {
+        assert!(calc_connors_rsi_impl(&sample_prices(), 3, 2, 9).is_empty());
+        assert!(calc_connors_rsi_impl(&sample_prices(), 0, 2, 3).is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // True Strength Index
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn tsi_of_steady_rise_is_100() {
+        let data = rising_prices(20);
+        let tsi = calc_tsi_impl(&data, 5, 3, 4);
+        // Momentum from bar 1; TSI from bar 5 + 3 - 1 = 7; signal 3 later.
+        assert_eq!(tsi.tsi.len(), 13);
+        assert_eq!(tsi.tsi[0].ts, data[7].ts);
+        assert_eq!(tsi.signal.len(), 10);
+        assert_eq!(tsi.signal[0].ts, data[10].ts);
+        assert!(tsi.tsi.iter().all(|p| (p.value - 100.0).abs() < 1e-9));
+        assert!(tsi.signal.iter().all(|p| (p.value - 100.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn tsi_flat_and_short_input() {
+        let flat: Vec<PricePoint> = (0..12)
+            .map(|i| pp(i as f64, 5.0, 5.0, 5.0, 5.0, 1.0))
+            .collect();
+        assert!(calc_tsi_impl(&flat, 5, 3, 2)
+            .tsi
+            .iter()
+            .all(|p| p.value == 0.0));
+        assert_eq!(calc_tsi_impl(&flat[..7], 5, 3, 2), TsiResult::default());
+        assert_eq!(calc_tsi_impl(&flat, 0, 3, 2), TsiResult::default());
+    }
+
+    // -----------------------------------------------------------------------
+    // Series diagnostics
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn describe_series_summarizes_finite_values() {
+        let points = vec![
+            ip(1.0, 4.0),
+            ip(2.0, f64::NAN),
+            ip(3.0, -2.0),
+            ip(4.0, f64::INFINITY),
+            ip(5.0, 7.0),
+        ];
+        let stats = describe_series_impl(&points);
+        assert_eq!(
+            stats,
+            SeriesStats {
+                count: 5,
+                first_ts: 1.0,
+                last_ts: 5.0,
+                min: -2.0,
+                max: 7.0,
+                mean: 3.0,
+                nan_count: 2,
+            }
+        );
+        assert_eq!(describe_series_impl(&[]), SeriesStats::default());
+    }
+
+    // -----------------------------------------------------------------------
+    // OHLC validation
+    // -----------------------------------------------------------------------
+
+    fn issues_of(bar: PricePoint) -> Vec<OhlcIssue> {
+        validate_ohlc_impl(&[bar])
+            .into_iter()
+            .map(|e| e.reason)
+            .collect()
+    }
+
+    #[test]
+    fn validate_ohlc_accepts_clean_bars() {
+        assert!(validate_ohlc_impl(&sample_prices()).is_empty());
+    }
+
+    #[test]
+    fn validate_ohlc_high_below_low() {
+        // Open and close sit inside neither ordering of the bounds.
+        assert_eq!(
+            issues_of(pp(0.0, 10.0, 9.0, 11.0, 10.0, 1.0)),
+            vec![
+                OhlcIssue::HighBelowLow,
+                OhlcIssue::OpenOutOfRange,
+                OhlcIssue::CloseOutOfRange
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_ohlc_open_out_of_range() {
+        assert_eq!(
+            issues_of(pp(0.0, 12.0, 11.0, 9.0, 10.0, 1.0)),
+            vec![OhlcIssue::OpenOutOfRange]
+        );
+    }
+
+    #[test]
+    fn validate_ohlc_close_out_of_range() {
+        assert_eq!(
+            issues_of(pp(0.0, 10.0, 11.0, 9.0, 8.5, 1.0)),
+            vec![OhlcIssue::CloseOutOfRange]
+        );
+    }
+
+    #[test]
+    fn validate_ohlc_non_finite_fields() {
+        assert_eq!(
+            issues_of(pp(0.0, 10.0, f64::NAN, 9.0, 10.0, 1.0)),
+            vec![OhlcIssue::NonFinite]
+        );
+        assert_eq!(
+            issues_of(pp(0.0, 10.0, 11.0, 9.0, 10.0, f64::INFINITY)),
+            vec![OhlcIssue::NonFinite]
+        );
+        let data = vec![
+            pp(0.0, 10.0, 11.0, 9.0, 10.0, 1.0),
+            pp(f64::NAN, 10.0, 11.0, 9.0, 10.0, 1.0),
+        ];
+        assert_eq!(
+            validate_ohlc_impl(&data),
+            vec![OhlcError {
+                index: 1,
+                reason: OhlcIssue::NonFinite
+            }]
+        );
     }
 
     // -----------------------------------------------------------------------
-    // SMA
+    // Portfolio value
     // -----------------------------------------------------------------------
 
-    #[test]
-    fn sma_normal_case() {
-        let data = sample_prices();
-        let result = calc_sma_impl(&data, 3);
-        // First value: avg of close[0..3] = (11+12+13)/3 = 12.0
-        assert_eq!(result.len(), 8);
-        assert!((result[0].value - 12.0).abs() < 1e-9);
-        assert_eq!(result[0].ts, 3.0);
-        // Second value: avg of close[1..4] = (12+13+14)/3 = 13.0
-        assert!((result[1].value - 13.0).abs() < 1e-9);
+    fn closes_at(bars: &[(f64, f64)]) -> Vec<PricePoint> {
+        bars.iter()
+            .map(|&(ts, c)| pp(ts, c, c, c, c, 1.0))
+            .collect()
     }
 
     #[test]
-    fn sma_period_greater_than_data() {
-        let data = sample_prices();
-        let result = calc_sma_impl(&data, 100);
-        assert!(result.is_empty());
-    }
+    fn portfolio_value_forward_fills_and_zeroes_before_ipo() {
+        let mut series = BTreeMap::new();
+        series.insert("AAA".to_string(), closes_at(&[(1.0, 10.0), (3.0, 12.0)]));
+        series.insert(
+            "BBB".to_string(),
+            closes_at(&[(2.0, 5.0), (3.0, 6.0), (4.0, 7.0)]),
+        );
+        series.insert("CCC".to_string(), closes_at(&[(5.0, 100.0)]));
+        let holdings = vec![
+            ("AAA".to_string(), 2.0),
+            ("BBB".to_string(), 10.0),
+            ("ZZZ".to_string(), 3.0),
+        ];
 
-    #[test]
-    fn sma_period_of_1() {
-        let data = sample_prices();
-        let result = calc_sma_impl(&data, 1);
-        assert_eq!(result.len(), data.len());
-        for (i, point) in result.iter().enumerate() {
-            assert!((point.value - data[i].close).abs() < 1e-9);
-        }
+        let value = portfolio_value_impl(&holdings, &series);
+        let got: Vec<(f64, f64)> = value.iter().map(|p| (p.ts, p.value)).collect();
+        // CCC is not held, so ts 5 is not in the union; ZZZ has no series.
+        assert_eq!(
+            got,
+            vec![(1.0, 20.0), (2.0, 70.0), (3.0, 84.0), (4.0, 94.0)]
+        );
+        assert!(portfolio_value_impl(&[], &series).is_empty());
     }
 
+    // -----------------------------------------------------------------------
+    // Rebasing
+    // -----------------------------------------------------------------------
+
     #[test]
-    fn sma_period_equals_data_len() {
-        let data = sample_prices();
-        let n = data.len();
-        let result = calc_sma_impl(&data, n);
-        assert_eq!(result.len(), 1);
-        let expected: f64 = data.iter().map(|p| p.close).sum::<f64>() / n as f64;
-        assert!((result[0].value - expected).abs() < 1e-9);
+    fn rebase_multi_starts_every_series_at_base() {
+        let a = closes_at(&[(1.0, 50.0), (2.0, 75.0)]);
+        let b = closes_at(&[(1.0, 200.0), (2.0, 180.0)]);
+        let zero = closes_at(&[(1.0, 0.0), (2.0, 5.0)]);
+        let rebased = rebase_multi_impl(&[a, b, zero], 100.0);
+        assert_eq!(rebased[0], vec![ip(1.0, 100.0), ip(2.0, 150.0)]);
+        assert_eq!(rebased[1], vec![ip(1.0, 100.0), ip(2.0, 90.0)]);
+        assert!(rebased[2].is_empty());
     }
 
     // -----------------------------------------------------------------------
-    // EMA
+    // Index-aligned moving averages
     // -----------------------------------------------------------------------
 
     #[test]
-    fn ema_normal_case() {
+    fn sma_aligned_pads_warmup_with_nan() {
         let data = sample_prices();
-        let result = calc_ema_impl(&data, 3);
-        assert_eq!(result.len(), 8);
-        // First EMA value must equal the SMA of the first 3 closes.
-        let first_sma = (11.0 + 12.0 + 13.0) / 3.0;
-        assert!((result[0].value - first_sma).abs() < 1e-9);
-    }
+        let aligned = calc_sma_aligned_impl(&data, 3);
+        assert_eq!(aligned.len(), data.len());
+        for (p, bar) in aligned.iter().zip(&data) {
+            assert_eq!(p.ts, bar.ts);
+        }
+        assert!(aligned[..2].iter().all(|p| p.value.is_nan()));
+        assert_eq!(aligned[2..], calc_sma_impl(&data, 3)[..]);
 
-    #[test]
-    fn ema_first_value_matches_sma() {
-        let data = sample_prices();
-        let period = 5;
-        let ema_result = calc_ema_impl(&data, period);
-        let sma_result = calc_sma_impl(&data, period);
-        // The first EMA value should equal the first SMA value.
-        assert!(
-            (ema_result[0].value - sma_result[0].value).abs() < 1e-9,
-            "First EMA ({}) should match first SMA ({})",
-            ema_result[0].value,
-            sma_result[0].value
+        assert!(calc_sma_aligned_impl(&data, 11)
+            .iter()
+            .all(|p| p.value.is_nan()));
+        assert_eq!(
+            calc_ema_aligned_impl(&data, 4)[3..],
+            calc_ema_impl(&data, 4)[..]
         );
     }
 
+    // -----------------------------------------------------------------------
+    // Median and percentile filters
+    // -----------------------------------------------------------------------
+
     #[test]
-    fn ema_period_greater_than_data() {
-        let data = sample_prices();
-        let result = calc_ema_impl(&data, 100);
-        assert!(result.is_empty());
+    fn median_filter_ignores_single_spike() {
+        let mut data: Vec<PricePoint> = (0..7)
+            .map(|i| pp(i as f64, 10.0, 10.0, 10.0, 10.0, 1.0))
+            .collect();
+        data[3].close = 500.0;
+        let median = calc_median_filter_impl(&data, 3, PriceSource::Close);
+        assert_eq!(median.len(), 5);
+        assert_eq!(median[0].ts, 2.0);
+        assert!(median.iter().all(|p| p.value == 10.0));
+        assert!(calc_sma_impl(&data, 3).iter().any(|p| p.value > 10.0));
     }
 
     #[test]
-    fn ema_multiplier_correctness() {
-        let data = sample_prices();
-        let period = 3;
-        let k = 2.0 / (period as f64 + 1.0); // 0.5
-        let result = calc_ema_impl(&data, period);
-        // Verify second EMA value manually.
-        let first_ema = (11.0 + 12.0 + 13.0) / 3.0; // 12.0
-        let second_ema = data[3].close * k + first_ema * (1.0 - k); // 14*0.5 + 12*0.5 = 13.0
-        assert!((result[1].value - second_ema).abs() < 1e-9);
+    fn percentile_filter_interpolates() {
+        let data = closes_at(&[(0.0, 4.0), (1.0, 1.0), (2.0, 3.0), (3.0, 2.0)]);
+        let at = |pct| calc_percentile_filter_impl(&data, 4, pct)[0].value;
+        assert_eq!(at(0.0), 1.0);
+        assert_eq!(at(100.0), 4.0);
+        assert_eq!(at(50.0), 2.5);
+        assert_eq!(at(25.0), 1.75);
+        assert!(calc_percentile_filter_impl(&data, 4, 101.0).is_empty());
+        assert!(calc_percentile_filter_impl(&data, 0, 50.0).is_empty());
     }
 
     // -----------------------------------------------------------------------
-    // RSI
+    // RSI divergence
     // -----------------------------------------------------------------------
 
-    #[test]
-    fn rsi_normal_case() {
-        let data = sample_prices();
-        let result = calc_rsi_impl(&data, 5);
-        assert!(!result.is_empty());
-        for point in &result {
-            assert!(point.value >= 0.0 && point.value <= 100.0);
-        }
+    fn closes_with_range(closes: &[f64]) -> Vec<PricePoint> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| pp(i as f64, c, c + 0.5, c - 0.5, c, 1.0))
+            .collect()
     }
 
+    /// A strong rally to a high at bar 5, a pullback, then a weaker push to
+    /// a slightly higher high at bar 11.
+    const WEAKENING_RALLY: [f64; 15] = [
+        10.0, 12.0, 14.0, 16.0, 18.0, 20.0, 18.0, 16.0, 17.0, 18.0, 19.0, 20.5, 19.0, 18.0, 17.0,
+    ];
+
     #[test]
-    fn rsi_all_gains() {
-        // Monotonically increasing closes -> RSI = 100.
-        let data: Vec<PricePoint> = (0..10)
-            .map(|i| pp(i as f64, 0.0, 0.0, 0.0, 10.0 + i as f64, 100.0))
-            .collect();
-        let result = calc_rsi_impl(&data, 5);
-        assert!(!result.is_empty());
-        for point in &result {
-            assert!(
-                (point.value - 100.0).abs() < 1e-9,
-                "Expected RSI=100, got {}",
-                point.value
-            );
-        }
+    fn rsi_divergence_bearish_higher_high() {
+        let data = closes_with_range(&WEAKENING_RALLY);
+        let events = detect_rsi_divergence_impl(&data, 3, 2, 2);
+        assert_eq!(
+            events,
+            vec![DivergenceEvent {
+                first_ts: 5.0,
+                second_ts: 11.0,
+                kind: DivergenceKind::Bearish,
+            }]
+        );
     }
 
     #[test]
-    fn rsi_all_losses() {
-        // Monotonically decreasing closes -> RSI = 0.
-        let data: Vec<PricePoint> = (0..10)
-            .map(|i| pp(i as f64, 0.0, 0.0, 0.0, 100.0 - i as f64, 100.0))
-            .collect();
-        let result = calc_rsi_impl(&data, 5);
-        assert!(!result.is_empty());
-        for point in &result {
-            assert!(
-                point.value.abs() < 1e-9,
-                "Expected RSI=0, got {}",
-                point.value
-            );
-        }
+    fn rsi_divergence_bullish_lower_low() {
+        let mirrored: Vec<f64> = WEAKENING_RALLY.iter().map(|c| 40.0 - c).collect();
+        let events = detect_rsi_divergence_impl(&closes_with_range(&mirrored), 3, 2, 2);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, DivergenceKind::Bullish);
+        assert_eq!((events[0].first_ts, events[0].second_ts), (5.0, 11.0));
     }
 
     #[test]
-    fn rsi_insufficient_data() {
-        let data = vec![pp(1.0, 0.0, 0.0, 0.0, 10.0, 100.0)];
-        let result = calc_rsi_impl(&data, 5);
-        assert!(result.is_empty());
+    fn rsi_divergence_insufficient_data() {
+        let data = closes_with_range(&WEAKENING_RALLY[..3]);
+        assert!(detect_rsi_divergence_impl(&data, 3, 2, 2).is_empty());
     }
 
     // -----------------------------------------------------------------------
-    // VWAP
+    // Half-life EMA
     // -----------------------------------------------------------------------
 
     #[test]
-    fn vwap_normal_case() {
+    fn halflife_ema_matches_period_ema_with_same_alpha() {
+        // Period 9 has k = 0.2, so 0.5^(1 / h) = 0.8.
+        let half_life = 0.5_f64.ln() / 0.8_f64.ln();
         let data = sample_prices();
-        let result = calc_vwap_impl(&data);
-        assert_eq!(result.len(), data.len());
-
-        // First VWAP value: typical = (12+9+11)/3 = 32/3 ≈ 10.6667
-        let tp0 = (12.0 + 9.0 + 11.0) / 3.0;
-        assert!(
-            (result[0].value - tp0).abs() < 1e-4,
-            "Expected ~{}, got {}",
-            tp0,
-            result[0].value
-        );
+        let by_half_life = calc_ema_halflife_impl(&data, half_life);
+        let by_period = calc_ema_seeded_impl(&data, 9, EmaSeed::FirstValue);
+        assert_points_close(&by_half_life, &by_period);
     }
 
     #[test]
-    fn vwap_single_point() {
-        let data = vec![pp(1.0, 10.0, 15.0, 5.0, 12.0, 1000.0)];
-        let result = calc_vwap_impl(&data);
-        assert_eq!(result.len(), 1);
-        let expected_tp = (15.0 + 5.0 + 12.0) / 3.0; // ~10.6667
-        assert!((result[0].value - expected_tp).abs() < 1e-9);
+    fn halflife_ema_rejects_non_positive() {
+        let data = sample_prices();
+        assert!(calc_ema_halflife_impl(&data, 0.0).is_empty());
+        assert!(calc_ema_halflife_impl(&data, -3.0).is_empty());
+        assert!(calc_ema_halflife_impl(&data, f64::NAN).is_empty());
     }
 
+    // -----------------------------------------------------------------------
+    // Underwater series
+    // -----------------------------------------------------------------------
+
     #[test]
-    fn vwap_empty() {
-        let result = calc_vwap_impl(&[]);
-        assert!(result.is_empty());
+    fn rolling_drawdown_resets_at_new_high() {
+        let data = closes_at(&[
+            (0.0, 100.0),
+            (1.0, 80.0),
+            (2.0, 90.0),
+            (3.0, 110.0),
+            (4.0, 99.0),
+        ]);
+        let underwater = calc_rolling_drawdown_impl(&data);
+        let values: Vec<f64> = underwater.iter().map(|p| p.value).collect();
+        assert_eq!(values[0], 0.0);
+        assert!((values[1] + 0.2).abs() < 1e-12);
+        assert!((values[2] + 0.1).abs() < 1e-12);
+        assert_eq!(values[3], 0.0);
+        assert!((values[4] + 0.1).abs() < 1e-12);
+        assert!(calc_rolling_drawdown_impl(&[]).is_empty());
     }
 
+    // -----------------------------------------------------------------------
+    // Prior session levels
+    // -----------------------------------------------------------------------
+
     #[test]
-    fn vwap_cumulative_behaviour() {
+    fn prior_session_levels_carry_previous_range() {
         let data = vec![
-            pp(1.0, 0.0, 30.0, 10.0, 20.0, 100.0),
-            pp(2.0, 0.0, 60.0, 20.0, 40.0, 200.0),
+            pp(10.0, 5.0, 6.0, 4.0, 5.5, 1.0),
+            pp(11.0, 5.5, 8.0, 5.0, 7.0, 1.0),
+            pp(20.0, 7.0, 7.5, 3.0, 3.5, 1.0),
+            pp(40.0, 4.0, 4.5, 3.5, 4.0, 1.0),
         ];
-        let result = calc_vwap_impl(&data);
-        // Point 1: tp = (30+10+20)/3 = 20, cum_tp_vol = 2000, cum_vol = 100, vwap = 20
-        assert!((result[0].value - 20.0).abs() < 1e-9);
-        // Point 2: tp = (60+20+40)/3 = 40, cum_tp_vol = 2000+8000 = 10000, cum_vol = 300
-        let expected = 10000.0 / 300.0;
-        assert!((result[1].value - expected).abs() < 1e-9);
+        // The session at 30 has no bars, so 40 still refers to 20's session.
+        let levels = prior_session_levels_impl(&data, &[10.0, 20.0, 30.0, 40.0]);
+        let lv = |session_ts, prev_high, prev_low, prev_close| SessionLevels {
+            session_ts,
+            prev_high,
+            prev_low,
+            prev_close,
+        };
+        assert_eq!(
+            levels,
+            vec![
+                lv(20.0, 8.0, 4.0, 7.0),
+                lv(30.0, 7.5, 3.0, 3.5),
+                lv(40.0, 7.5, 3.0, 3.5),
+            ]
+        );
+        assert!(prior_session_levels_impl(&data, &[10.0]).is_empty());
     }
 
     // -----------------------------------------------------------------------
-    // filter_symbols
+    // Multi-symbol batch
     // -----------------------------------------------------------------------
 
-    fn sample_entries() -> Vec<SymbolEntry> {
+    fn many_symbols() -> Vec<Vec<PricePoint>> {
         vec![
-            SymbolEntry {
-                symbol: "AAPL".to_string(),
-                name: "Apple Inc.".to_string(),
-            },
-            SymbolEntry {
-                symbol: "MSFT".to_string(),
-                name: "Microsoft Corporation".to_string(),
-            },
-            SymbolEntry {
-                symbol: "AMZN".to_string(),
-                name: "Amazon.com Inc.".to_string(),
-            },
-            SymbolEntry {
-                symbol: "GOOG".to_string(),
-                name: "Alphabet Inc.".to_string(),
-            },
-            SymbolEntry {
-                symbol: "META".to_string(),
-                name: "Meta Platforms Inc.".to_string(),
-            },
-            SymbolEntry {
-                symbol: "TSLA".to_string(),
-                name: "Tesla Inc.".to_string(),
-            },
-            SymbolEntry {
-                symbol: "AA".to_string(),
-                name: "Alcoa Corporation".to_string(),
-            },
+            sample_prices(),
+            zigzag_prices(),
+            rising_prices(40),
+            Vec::new(),
         ]
     }
 
     #[test]
-    fn filter_exact_match_scores_highest() {
-        let entries = sample_entries();
-        let result = filter_symbols_impl(&entries, "AAPL", 10);
-        assert!(!result.is_empty());
-        assert_eq!(result[0].symbol, "AAPL");
+    fn compute_many_runs_spec_per_symbol() {
+        let symbols = many_symbols();
+        let out = compute_many_impl(&symbols, &spec("ema", 5));
+        assert_eq!(out.len(), symbols.len());
+        for (series, data) in out.iter().zip(&symbols) {
+            assert_eq!(*series, calc_ema_impl(data, 5));
+        }
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn filter_prefix_match() {
-        let entries = sample_entries();
-        let result = filter_symbols_impl(&entries, "AA", 10);
-        // "AA" exact match on AA (100), "AAPL" starts with "AA" (80)
-        assert!(result.len() >= 2);
-        assert_eq!(result[0].symbol, "AA", "Exact match should rank first");
-        assert_eq!(result[1].symbol, "AAPL", "Prefix match should rank second");
+    fn compute_many_parallel_matches_sequential() {
+        let symbols = many_symbols();
+        for s in [spec("sma", 3), spec("rsi", 5), spec("vwap", 0)] {
+            let sequential: Vec<Vec<IndicatorPoint>> = symbols
+                .iter()
+                .map(|data| compute_indicator_impl(data, &s))
+                .collect();
+            assert_eq!(compute_many_impl(&symbols, &s), sequential);
+        }
     }
 
-    #[test]
-    fn filter_no_match() {
-        let entries = sample_entries();
-        let result = filter_symbols_impl(&entries, "XYZ", 10);
-        assert!(result.is_empty());
-    }
+    // -----------------------------------------------------------------------
+    // Relative performance
+    // -----------------------------------------------------------------------
 
     #[test]
-    fn filter_case_insensitivity() {
-        let entries = sample_entries();
-        let result = filter_symbols_impl(&entries, "aapl", 10);
-        assert!(!result.is_empty());
-        assert_eq!(result[0].symbol, "AAPL");
+    fn relative_performance_rebases_at_first_shared_bar() {
+        // The asset's bar at 0 has no benchmark bar, so both rebase at 1.
+        let asset = closes_at(&[(0.0, 1.0), (1.0, 50.0), (2.0, 60.0), (3.0, 55.0)]);
+        let benchmark = closes_at(&[(1.0, 200.0), (2.0, 210.0), (3.0, 230.0), (4.0, 240.0)]);
+        let rel = calc_relative_performance_impl(&asset, &benchmark);
+        let ts: Vec<f64> = rel.iter().map(|p| p.ts).collect();
+        assert_eq!(ts, vec![1.0, 2.0, 3.0]);
+        assert_eq!(rel[0].value, 0.0);
+        assert!((rel[1].value - 15.0).abs() < 1e-9);
+        assert!((rel[2].value + 5.0).abs() < 1e-9);
     }
 
     #[test]
-    fn filter_name_match() {
-        let entries = sample_entries();
-        let result = filter_symbols_impl(&entries, "Tesla", 10);
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].symbol, "TSLA");
+    fn relative_performance_without_overlap_is_empty() {
+        let asset = closes_at(&[(0.0, 1.0)]);
+        let benchmark = closes_at(&[(1.0, 1.0)]);
+        assert!(calc_relative_performance_impl(&asset, &benchmark).is_empty());
     }
 
+    // -----------------------------------------------------------------------
+    // Minimum search score
+    // -----------------------------------------------------------------------
+
     #[test]
-    fn filter_max_results_limit() {
-        let entries = sample_entries();
-        let result = filter_symbols_impl(&entries, "a", 2);
-        assert_eq!(result.len(), 2);
+    fn min_score_drops_name_contains_keeps_name_prefix() {
+        let entries = vec![
+            named("MU", "Micron Technology"),
+            named("QCOM", "Qualcomm Inc"),
+            named("MCHP", "Microchip Technology"),
+        ];
+        // "tech" only occurs mid-name; "micro" starts two names.
+        let loose = filter_symbols_min_impl(&entries, "tech", 10, 0);
+        assert_eq!(loose.len(), 2);
+        assert!(filter_symbols_min_impl(&entries, "tech", 10, 40).is_empty());
+
+        let strict = filter_symbols_min_impl(&entries, "micro", 10, 40);
+        let symbols: Vec<&str> = strict.iter().map(|e| e.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["MCHP", "MU"]);
+        assert_eq!(
+            filter_symbols_min_impl(&entries, "micro", 10, 0),
+            filter_symbols_impl(&entries, "micro", 10)
+        );
     }
 
+    // -----------------------------------------------------------------------
+    // Renko
+    // -----------------------------------------------------------------------
+
     #[test]
-    fn filter_name_contains() {
-        let entries = sample_entries();
-        // "form" is contained in "Meta Platforms Inc." -> score 20
-        let result = filter_symbols_impl(&entries, "Platforms", 10);
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0].symbol, "META");
+    fn renko_bricks_need_two_sizes_to_reverse() {
+        let data = closes_at(&[
+            (0.0, 100.0),
+            (1.0, 103.5),
+            (2.0, 101.5),
+            (3.0, 100.0),
+            (4.0, 98.5),
+        ]);
+        let bricks = calc_renko_impl(&data, 1.0);
+        let summary: Vec<(f64, f64, bool)> = bricks.iter().map(|b| (b.ts, b.close, b.up)).collect();
+        // Up to 103 on bar 1; 101.5 is only 1.5 below the 102 bottom; 100
+        // reverses down through 101 and 100; 98.5 adds 99.
+        assert_eq!(
+            summary,
+            vec![
+                (1.0, 101.0, true),
+                (1.0, 102.0, true),
+                (1.0, 103.0, true),
+                (3.0, 101.0, false),
+                (3.0, 100.0, false),
+                (4.0, 99.0, false),
+            ]
+        );
+        assert!(calc_renko_impl(&data, 0.0).is_empty());
     }
 
     #[test]
-    fn filter_empty_query_returns_up_to_max() {
-        let entries = sample_entries();
-        let result = filter_symbols_impl(&entries, "", 3);
-        assert_eq!(result.len(), 3);
+    fn renko_trend_and_reversals() {
+        let data = closes_at(&[
+            (0.0, 100.0),
+            (1.0, 103.5),
+            (2.0, 100.0),
+            (3.0, 98.5),
+            (4.0, 101.0),
+        ]);
+        let bricks = calc_renko_impl(&data, 1.0);
+        assert_eq!(renko_trend_impl(&bricks), vec![1, 2, 3, -1, -2, -3, 1]);
+        assert_eq!(renko_reversals_impl(&bricks), vec![2.0, 4.0]);
+        assert!(renko_trend_impl(&[]).is_empty());
+        assert!(renko_reversals_impl(&[]).is_empty());
     }
 
     #[test]
-    fn filter_symbol_contains() {
-        let entries = sample_entries();
-        // "OO" is contained in "GOOG" -> symbol contains = 60
-        let result = filter_symbols_impl(&entries, "OO", 10);
-        assert!(!result.is_empty());
-        assert_eq!(result[0].symbol, "GOOG");
+    fn renko_terminates_on_extreme_closes() {
+        // At 1e17 adding 1.0 is lost to rounding; brick by brick this looped
+        // forever.
+        let data = closes_at(&[(0.0, 1e17), (1.0, 1e17 + 64.0)]);
+        let bricks = calc_renko_impl(&data, 1.0);
+        assert_eq!(bricks.len(), 64);
+        assert!(bricks.iter().all(|b| b.up && b.ts == 1.0));
+
+        // Non-finite closes are skipped, including a non-finite first close.
+        let data = closes_at(&[
+            (0.0, f64::NAN),
+            (1.0, 100.0),
+            (2.0, f64::INFINITY),
+            (3.0, 102.5),
+            (4.0, f64::NEG_INFINITY),
+        ]);
+        let closes: Vec<f64> = calc_renko_impl(&data, 1.0)
+            .iter()
+            .map(|b| b.close)
+            .collect();
+        assert_eq!(closes, vec![101.0, 102.0]);
+
+        let data = closes_at(&[(0.0, 0.0), (1.0, 1e12)]);
+        assert_eq!(calc_renko_impl(&data, 1e-3).len(), MAX_RENKO_BRICKS);
     }
 }